@@ -1,14 +1,201 @@
-use quote::quote;
-use syn::DeriveInput;
+use agsol_borsh_schema_parse::{is_repr_c, SchemaOptions, Seed};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, LitByteStr, Type};
 
-#[proc_macro_derive(BorshSchema, attributes(alias, schema_skip))]
+#[proc_macro_derive(BorshSchema, attributes(alias, schema, schema_skip, seeds))]
 pub fn borsh_schema(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
+    let name = &input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
+    let seed_impl = match input.attrs.iter().find(|attr| attr.path.is_ident("seeds")) {
+        Some(attr) => match impl_seeds(&input, attr) {
+            Ok(stream) => stream,
+            Err(err) => err.to_compile_error(),
+        },
+        None => quote! {},
+    };
+
+    let options_impl = match SchemaOptions::from_attributes(&name.to_string(), &input.attrs) {
+        Ok(options) => match impl_zero_copy(&input, &options) {
+            Ok(zero_copy_impl) => {
+                let discriminator_impl = impl_discriminator(&input, &options);
+                quote! {
+                    #zero_copy_impl
+                    #discriminator_impl
+                }
+            }
+            Err(err) => err.to_compile_error(),
+        },
+        Err(err) => err.to_compile_error(),
+    };
+
     let token_stream2 = quote! {
         impl #impl_generics BorshSchema for #name #ty_generics #where_clause {}
+        #seed_impl
+        #options_impl
     };
     token_stream2.into()
 }
+
+/// Generates `seeds` and `find_address` associated functions (and a
+/// `SEED_PREFIX` constant if the recipe starts with a literal) from a
+/// `#[seeds(...)]` attribute.
+fn impl_seeds(input: &DeriveInput, attr: &syn::Attribute) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let seeds = Seed::from_attribute(attr)?;
+
+    let mut params = Vec::new();
+    let mut param_names = Vec::new();
+    let mut seed_bytes = Vec::new();
+    for seed in seeds.iter() {
+        match seed {
+            Seed::Literal(bytes) => {
+                let literal = LitByteStr::new(bytes, Span::call_site());
+                seed_bytes.push(quote! { #literal.to_vec() });
+            }
+            Seed::Pubkey(name) => {
+                params.push(quote! { #name: &::solana_program::pubkey::Pubkey });
+                param_names.push(name);
+                seed_bytes.push(quote! { #name.as_ref().to_vec() });
+            }
+            Seed::Integer {
+                name,
+                ty,
+                big_endian,
+            } => {
+                params.push(quote! { #name: #ty });
+                param_names.push(name);
+                if *big_endian {
+                    seed_bytes.push(quote! { #name.to_be_bytes().to_vec() });
+                } else {
+                    seed_bytes.push(quote! { #name.to_le_bytes().to_vec() });
+                }
+            }
+        }
+    }
+
+    let seed_prefix = match seeds.first() {
+        Some(Seed::Literal(bytes)) => {
+            let literal = LitByteStr::new(bytes, Span::call_site());
+            quote! { pub const SEED_PREFIX: &'static [u8] = #literal; }
+        }
+        _ => quote! {},
+    };
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #seed_prefix
+
+            /// Returns the PDA seeds declared in the `seeds` attribute.
+            pub fn seeds(#(#params),*) -> Vec<Vec<u8>> {
+                vec![#(#seed_bytes),*]
+            }
+
+            /// Finds the PDA (and its bump seed) declared in the `seeds`
+            /// attribute.
+            pub fn find_address(
+                program_id: &::solana_program::pubkey::Pubkey,
+                #(#params),*
+            ) -> (::solana_program::pubkey::Pubkey, u8) {
+                let seeds = Self::seeds(#(#param_names),*);
+                let seeds = seeds.iter().map(|seed| seed.as_slice()).collect::<Vec<&[u8]>>();
+                ::solana_program::pubkey::Pubkey::find_program_address(&seeds, program_id)
+            }
+        }
+    })
+}
+
+/// Generates a `DISCRIMINATOR` constant if the data structure is annotated
+/// with `#[schema(discriminator)]`.
+fn impl_discriminator(input: &DeriveInput, options: &SchemaOptions) -> TokenStream2 {
+    let discriminator = match &options.discriminator {
+        Some(discriminator) => LitByteStr::new(discriminator, Span::call_site()),
+        None => return quote! {},
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Bytes prepended to the serialized account data.
+            pub const DISCRIMINATOR: &'static [u8] = #discriminator;
+        }
+    }
+}
+
+/// Checks that a data structure annotated with `#[schema(zero_copy)]` is a
+/// `#[repr(C)]` struct of POD-compatible fields and implements
+/// `ZeroCopySchema` for it.
+///
+/// Fields must be integers, `Pubkey`s, arrays of them or zero-copy layouts
+/// themselves, the latter being asserted via a `ZeroCopySchema` bound.
+fn impl_zero_copy(input: &DeriveInput, options: &SchemaOptions) -> syn::Result<TokenStream2> {
+    if !options.zero_copy {
+        return Ok(quote! {});
+    }
+
+    if !is_repr_c(&input.attrs)? {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "zero-copy layouts must be `#[repr(C)]` and not packed",
+        ));
+    }
+
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "zero-copy layouts must be structs",
+            ))
+        }
+    };
+    let mut layout_types = Vec::new();
+    for field in data.fields.iter() {
+        collect_layout_types(&field.ty, &mut layout_types)?;
+    }
+    let assertions = layout_types.iter().map(|ty| {
+        quote_spanned! {ty.span()=>
+            assert_zero_copy::<#ty>();
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::agsol_borsh_schema::ZeroCopySchema for #name #ty_generics #where_clause {}
+
+        const _: () = {
+            fn assert_zero_copy<T: ::agsol_borsh_schema::ZeroCopySchema>() {}
+            #[allow(dead_code)]
+            fn assert_fields #impl_generics () #where_clause {
+                #(#assertions)*
+            }
+        };
+    })
+}
+
+/// Collects the element types of a field that are neither integers nor
+/// `Pubkey`s, and thus have to be zero-copy layouts themselves.
+fn collect_layout_types<'a>(ty: &'a Type, layout_types: &mut Vec<&'a Type>) -> syn::Result<()> {
+    match ty {
+        Type::Array(array) => collect_layout_types(&array.elem, layout_types),
+        Type::Path(path) if path.qself.is_none() => {
+            let segment = path.path.segments.last().unwrap();
+            match segment.ident.to_string().as_str() {
+                "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128"
+                | "Pubkey"
+                    if segment.arguments.is_empty() => {}
+                _ => layout_types.push(ty),
+            }
+            Ok(())
+        }
+        _ => Err(syn::Error::new_spanned(
+            ty,
+            "type is not POD-compatible and cannot be part of a zero-copy layout",
+        )),
+    }
+}
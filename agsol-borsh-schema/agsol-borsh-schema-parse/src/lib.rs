@@ -0,0 +1,371 @@
+//! Parsers of the attributes understood by both the `BorshSchema` derive
+//! macro and the schema generator of `agsol-borsh-schema`, so that the two
+//! always accept the same input. The discriminator parsers are shared with
+//! the `AccountState` derive of `agsol-common` as well.
+
+use quote::ToTokens;
+use sha2::{Digest, Sha256};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, Lit, LitByteStr, LitStr, Meta, NestedMeta, Token};
+
+/// Length of an Anchor account discriminator.
+const ANCHOR_DISCRIMINATOR_LEN: usize = 8;
+/// Maximum length of a single PDA seed, see
+/// `solana_program::pubkey::MAX_SEED_LEN`.
+const MAX_SEED_LEN: usize = 32;
+/// Maximum number of PDA seeds that fit next to the bump seed, see
+/// `solana_program::pubkey::MAX_SEEDS`.
+const MAX_SEEDS: usize = 15;
+
+/// Computes the Anchor account discriminator of a data structure, i.e. the
+/// first 8 bytes of `sha256("account:<Name>")`.
+pub fn anchor_discriminator(name: &str) -> Vec<u8> {
+    Sha256::digest(format!("account:{}", name).as_bytes())[..ANCHOR_DISCRIMINATOR_LEN].to_vec()
+}
+
+/// Parses a custom discriminator given either as a hex string
+/// (`"0a1b2c3d4e5f6a7b"`) or a byte string (`b"tagbytes"`).
+pub fn parse_discriminator(lit: &Lit) -> syn::Result<Vec<u8>> {
+    let discriminator = match lit {
+        Lit::ByteStr(bytes) => bytes.value(),
+        Lit::Str(hex) => {
+            let value = hex.value();
+            let digits = value.strip_prefix("0x").unwrap_or(&value);
+            // checked up front so that slicing by byte index below is sound
+            if !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+                return Err(syn::Error::new_spanned(lit, "invalid hex discriminator"));
+            }
+            if digits.len() % 2 != 0 {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "hex discriminator must have an even number of digits",
+                ));
+            }
+            (0..digits.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| syn::Error::new_spanned(lit, "invalid hex discriminator"))?
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                lit,
+                "discriminator must be a hex string or a byte string",
+            ))
+        }
+    };
+    if discriminator.is_empty() {
+        return Err(syn::Error::new_spanned(
+            lit,
+            "discriminator cannot be empty",
+        ));
+    }
+    Ok(discriminator)
+}
+
+/// Options set via the `schema` attribute of a data structure.
+#[derive(Debug, Default, PartialEq)]
+pub struct SchemaOptions {
+    pub zero_copy: bool,
+    pub discriminator: Option<Vec<u8>>,
+}
+
+impl SchemaOptions {
+    /// Collects the options of all `#[schema(...)]` attributes of the data
+    /// structure called `name`.
+    pub fn from_attributes(name: &str, attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("schema")) {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(syn::Error::new_spanned(meta, "expected `#[schema(...)]`")),
+            };
+            for nested in list.nested.iter() {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("zero_copy") => {
+                        options.zero_copy = true
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("discriminator") => {
+                        options.discriminator = Some(anchor_discriminator(name))
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("discriminator") =>
+                    {
+                        options.discriminator = Some(parse_discriminator(&name_value.lit)?)
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            nested,
+                            format!("unknown schema option `{}`", nested.to_token_stream()),
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Checks whether a data structure has an unpacked C compatible memory
+/// layout.
+pub fn is_repr_c(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut c_compatible = false;
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        let reprs = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        for repr in reprs.iter() {
+            // `packed(n)` and `align(n)` are lists
+            if repr.path().is_ident("C") || repr.path().is_ident("transparent") {
+                c_compatible = true;
+            } else if repr.path().is_ident("packed") {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(c_compatible)
+}
+
+/// A single component of a PDA seed recipe declared via the `seeds`
+/// attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Seed {
+    /// Literal bytes, e.g. `b"pool"` or `"pool"`.
+    Literal(Vec<u8>),
+    /// A public key parameter, e.g. `authority: Pubkey`.
+    Pubkey(Ident),
+    /// An integer parameter encoded with the given endianness, e.g.
+    /// `index: u64` (little endian) or `index: u64(be)`.
+    Integer {
+        name: Ident,
+        ty: Ident,
+        big_endian: bool,
+    },
+}
+
+impl Parse for Seed {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let literal = if input.peek(LitByteStr) {
+            let literal = input.parse::<LitByteStr>()?;
+            Some((literal.value(), literal.span()))
+        } else if input.peek(LitStr) {
+            let literal = input.parse::<LitStr>()?;
+            Some((literal.value().into_bytes(), literal.span()))
+        } else {
+            None
+        };
+        if let Some((bytes, span)) = literal {
+            if bytes.len() > MAX_SEED_LEN {
+                return Err(syn::Error::new(
+                    span,
+                    format!("seeds can be at most {} bytes long", MAX_SEED_LEN),
+                ));
+            }
+            return Ok(Self::Literal(bytes));
+        }
+
+        let name = input.parse::<Ident>()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse::<Ident>()?;
+        let endianness = if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            Some(content.parse::<Ident>()?)
+        } else {
+            None
+        };
+        let big_endian = match &endianness {
+            Some(endianness) => match endianness.to_string().as_str() {
+                "le" => false,
+                "be" => true,
+                _ => {
+                    return Err(syn::Error::new(
+                        endianness.span(),
+                        "expected endianness `le` or `be`",
+                    ))
+                }
+            },
+            None => false,
+        };
+
+        match ty.to_string().as_str() {
+            "Pubkey" => match endianness {
+                Some(endianness) => Err(syn::Error::new(
+                    endianness.span(),
+                    "`Pubkey` seeds don't have an endianness",
+                )),
+                None => Ok(Self::Pubkey(name)),
+            },
+            "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128" => {
+                Ok(Self::Integer {
+                    name,
+                    ty,
+                    big_endian,
+                })
+            }
+            _ => Err(syn::Error::new(
+                ty.span(),
+                "seed parameters must be `Pubkey` or integer types",
+            )),
+        }
+    }
+}
+
+impl Seed {
+    /// Parses the seed recipe from a `#[seeds(...)]` attribute.
+    pub fn from_attribute(attr: &syn::Attribute) -> syn::Result<Vec<Self>> {
+        let seeds = attr.parse_args_with(Punctuated::<Self, Token![,]>::parse_terminated)?;
+        if seeds.len() > MAX_SEEDS {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!("at most {} seeds fit next to the bump seed", MAX_SEEDS),
+            ));
+        }
+        Ok(seeds.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn anchor_discriminator_of_name() {
+        // sha256("account:Pool") starts with these bytes
+        let expected = Sha256::digest(b"account:Pool");
+        assert_eq!(anchor_discriminator("Pool"), &expected[..8]);
+        assert_eq!(anchor_discriminator("Pool").len(), ANCHOR_DISCRIMINATOR_LEN);
+    }
+
+    #[test]
+    fn custom_discriminators() {
+        let lit: Lit = syn::parse_quote! { "0x0a1B2c" };
+        assert_eq!(parse_discriminator(&lit).unwrap(), vec![0x0a, 0x1b, 0x2c]);
+        let lit: Lit = syn::parse_quote! { b"tag" };
+        assert_eq!(parse_discriminator(&lit).unwrap(), b"tag".to_vec());
+        let lit: Lit = syn::parse_quote! { "abc" };
+        assert!(parse_discriminator(&lit).is_err());
+        let lit: Lit = syn::parse_quote! { "zz" };
+        assert!(parse_discriminator(&lit).is_err());
+        // non-ASCII characters don't split on byte boundaries
+        for hex in ["aéb1", "0xé1", "+1"] {
+            let lit = Lit::Str(LitStr::new(hex, proc_macro2::Span::call_site()));
+            assert_eq!(
+                parse_discriminator(&lit).unwrap_err().to_string(),
+                "invalid hex discriminator"
+            );
+        }
+        let lit: Lit = syn::parse_quote! { "" };
+        assert!(parse_discriminator(&lit).is_err());
+        let lit: Lit = syn::parse_quote! { 12 };
+        assert!(parse_discriminator(&lit).is_err());
+    }
+
+    #[test]
+    fn schema_options() {
+        let item: syn::DeriveInput = syn::parse_quote! {
+            #[derive(BorshSchema)]
+            #[schema(zero_copy)]
+            #[schema(discriminator = b"tag")]
+            struct Foo;
+        };
+        assert_eq!(
+            SchemaOptions::from_attributes("Foo", &item.attrs).unwrap(),
+            SchemaOptions {
+                zero_copy: true,
+                discriminator: Some(b"tag".to_vec()),
+            }
+        );
+        let item: syn::DeriveInput = syn::parse_quote! { #[schema(discriminator)] struct Foo; };
+        assert_eq!(
+            SchemaOptions::from_attributes("Foo", &item.attrs)
+                .unwrap()
+                .discriminator,
+            Some(anchor_discriminator("Foo"))
+        );
+        let item: syn::DeriveInput = syn::parse_quote! { #[schema(unknown)] struct Foo; };
+        assert!(SchemaOptions::from_attributes("Foo", &item.attrs).is_err());
+        let item: syn::DeriveInput = syn::parse_quote! { #[schema = "zero_copy"] struct Foo; };
+        assert!(SchemaOptions::from_attributes("Foo", &item.attrs).is_err());
+    }
+
+    #[test]
+    fn repr_c_attributes() {
+        let item: syn::DeriveInput = syn::parse_quote! {
+            #[derive(Clone)]
+            #[repr(C, align(8))]
+            struct Foo;
+        };
+        assert!(is_repr_c(&item.attrs).unwrap());
+        let item: syn::DeriveInput = syn::parse_quote! { #[repr(transparent)] struct Foo; };
+        assert!(is_repr_c(&item.attrs).unwrap());
+
+        for item in [
+            syn::parse_quote! { struct Foo; },
+            syn::parse_quote! { #[repr(Custom)] struct Foo; },
+            syn::parse_quote! { #[repr(u8)] #[doc = "C"] struct Foo; },
+            syn::parse_quote! { #[repr(C, packed)] struct Foo; },
+            syn::parse_quote! { #[repr(C)] #[repr(packed(2))] struct Foo; },
+        ] {
+            let item: syn::DeriveInput = item;
+            assert!(!is_repr_c(&item.attrs).unwrap());
+        }
+        let item: syn::DeriveInput = syn::parse_quote! { #[repr = "C"] struct Foo; };
+        assert!(is_repr_c(&item.attrs).is_err());
+    }
+
+    #[test]
+    fn seed_attribute() {
+        let attr: syn::Attribute = syn::parse_quote! {
+            #[seeds(b"pool", "x", authority: Pubkey, delta: i64(be))]
+        };
+        let seeds = Seed::from_attribute(&attr).unwrap();
+        assert_eq!(seeds[0], Seed::Literal(b"pool".to_vec()));
+        assert_eq!(seeds[1], Seed::Literal(b"x".to_vec()));
+        assert!(matches!(&seeds[2], Seed::Pubkey(name) if name == "authority"));
+        assert!(matches!(
+            &seeds[3],
+            Seed::Integer { name, ty, big_endian: true } if name == "delta" && ty == "i64"
+        ));
+
+        for (attr, message) in [
+            (
+                syn::parse_quote! { #[seeds(name: String)] },
+                "seed parameters must be `Pubkey` or integer types",
+            ),
+            (
+                syn::parse_quote! { #[seeds(index: u8(middle))] },
+                "expected endianness `le` or `be`",
+            ),
+            (
+                syn::parse_quote! { #[seeds(owner: Pubkey(be))] },
+                "`Pubkey` seeds don't have an endianness",
+            ),
+            (
+                syn::parse_quote! { #[seeds(time: UnixTimestamp)] },
+                "seed parameters must be `Pubkey` or integer types",
+            ),
+            (
+                syn::parse_quote! { #[seeds(b"0123456789abcdef0123456789abcdef!")] },
+                "seeds can be at most 32 bytes long",
+            ),
+            (
+                syn::parse_quote! { #[seeds(a: u8, b: u8, c: u8, d: u8, e: u8, f: u8, g: u8, h: u8, i: u8, j: u8, k: u8, l: u8, m: u8, n: u8, o: u8, p: u8)] },
+                "at most 15 seeds fit next to the bump seed",
+            ),
+        ] {
+            let attr: syn::Attribute = attr;
+            assert_eq!(
+                Seed::from_attribute(&attr).unwrap_err().to_string(),
+                message
+            );
+        }
+        let attr: syn::Attribute = syn::parse_quote! {
+            #[seeds("0123456789abcdef0123456789abcdef", owner: Pubkey(le))]
+        };
+        assert!(Seed::from_attribute(&attr).is_err());
+        let attr: syn::Attribute = syn::parse_quote! {
+            #[seeds("0123456789abcdef0123456789abcdef", owner: Pubkey)]
+        };
+        assert_eq!(Seed::from_attribute(&attr).unwrap().len(), 2);
+    }
+}
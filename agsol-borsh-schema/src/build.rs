@@ -0,0 +1,141 @@
+//! Entry point for generating the schema from a `build.rs` build script.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! // build.rs
+//! agsol_borsh_schema::build_schema("./schema").unwrap();
+//! ```
+
+use crate::layout::Layout;
+use crate::utils::{generate_layout_from_file, generate_output};
+
+use proc_macro2::Span;
+
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An error that occurred while parsing a data structure, pointing at the
+/// offending source location.
+#[derive(Debug)]
+pub struct LayoutError {
+    pub file: PathBuf,
+    /// One-based line number.
+    pub line: usize,
+    /// One-based column number.
+    pub column: usize,
+    pub message: String,
+}
+
+impl LayoutError {
+    pub(crate) fn new(file: &Path, span: Span, message: String) -> Self {
+        let start = span.start();
+        Self {
+            file: file.to_owned(),
+            line: start.line,
+            column: start.column + 1,
+            message,
+        }
+    }
+
+    /// Locates an error that occurred while parsing an item. Errors coming
+    /// from `syn` carry their own span, everything else points at the name
+    /// of the item.
+    pub(crate) fn from_item(file: &Path, item: &syn::Item, error: anyhow::Error) -> Self {
+        let span = match error.downcast_ref::<syn::Error>() {
+            Some(syn_error) => syn_error.span(),
+            None => match item {
+                syn::Item::Struct(item_struct) => item_struct.ident.span(),
+                syn::Item::Enum(item_enum) => item_enum.ident.span(),
+                _ => Span::call_site(),
+            },
+        };
+        Self::new(file, span, error.to_string())
+    }
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// Generates the schema from the `src` directory of the crate being built.
+///
+/// Meant to be called from a build script: it emits `cargo:rerun-if-changed`
+/// for every scanned file, reports every invalid data structure as a
+/// `cargo:warning` pointing at its source location and only rewrites the
+/// output if its contents actually changed.
+pub fn build_schema(output_directory: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+    let manifest_directory = std::env::var("CARGO_MANIFEST_DIR")?;
+    build_schema_from(Path::new(&manifest_directory).join("src"), output_directory)
+}
+
+/// Same as [`build_schema`] but scans the provided source directory.
+pub fn build_schema_from(
+    source_directory: impl AsRef<Path>,
+    output_directory: impl AsRef<Path>,
+) -> Result<(), anyhow::Error> {
+    let source_directory = source_directory.as_ref();
+    println!("cargo:rerun-if-changed={}", source_directory.display());
+
+    let mut files = Vec::new();
+    collect_source_files(source_directory, &mut files)?;
+
+    let mut layouts = Vec::<Layout>::new();
+    let mut errors = Vec::<LayoutError>::new();
+    for file in &files {
+        println!("cargo:rerun-if-changed={}", file.display());
+        match generate_layout_from_file(file) {
+            Ok(mut file_layouts) => layouts.append(&mut file_layouts),
+            Err(error) => match error.downcast::<LayoutError>() {
+                Ok(layout_error) => errors.push(layout_error),
+                Err(error) => return Err(error),
+            },
+        }
+    }
+
+    if !errors.is_empty() {
+        for error in &errors {
+            println!("cargo:warning={}", error);
+        }
+        anyhow::bail!(
+            "failed to generate schema:\n{}",
+            errors
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    generate_output(&layouts, output_directory)
+}
+
+/// Recursively collects `.rs` files in a deterministic order, so that the
+/// generated output doesn't change between runs.
+fn collect_source_files(directory: &Path, files: &mut Vec<PathBuf>) -> Result<(), anyhow::Error> {
+    let mut paths = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            collect_source_files(&path, files)?;
+        } else if path.extension() == Some(OsStr::new("rs")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,463 @@
+//! Golden test-vector generation for checking that Rust and TypeScript agree
+//! on borsh layouts.
+//!
+//! Every generated fixture consists of a `<name>.json` file containing the
+//! expected value and a `<name>.hex` file containing its borsh-serialized
+//! bytes. Structs get a `min` (zeros, negative minimum signed integers, empty
+//! collections, `None`, first enum variant) and a `max` (maximum integers,
+//! filled collections, `Some`, last enum variant) fixture, while enums get a
+//! fixture for every variant.
+//! Signed integers are stored as `{ "$signed": value, "bits": bits }` in the
+//! JSON files, because `borsh-js` decodes them as their unsigned two's
+//! complement.
+//! Fixtures of zero-copy layouts contain their C memory layout (with zeroed
+//! padding) instead, which is decoded with the generated `decode<Name>`
+//! function.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use agsol_borsh_schema::{generate_golden_vectors, generate_layouts, generate_output};
+//! # fn generate_fixtures() -> Result<(), anyhow::Error> {
+//!     let layouts = generate_layouts("~/input-rust-directory")?;
+//!     generate_output(&layouts, "./output-directory")?;
+//!     generate_golden_vectors(&layouts, "./output-directory")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::layout::{c_layout, BorshType, Kind, Layout};
+
+use serde_json::{json, Map, Value};
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Nesting depth after which every value falls back to its minimal sample so
+/// that recursive layouts terminate.
+const MAX_DEPTH: usize = 16;
+/// Number of elements in a filled `Vec` or map.
+const COLLECTION_LEN: usize = 3;
+/// Subdirectory of the output directory containing the fixtures.
+const GOLDEN_DIRECTORY: &str = "golden";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Case {
+    Min,
+    Max,
+}
+
+/// A deterministic sample value of a layout.
+#[derive(Debug)]
+pub struct GoldenVector {
+    /// Name of the sampled layout.
+    pub layout: String,
+    /// Name of the fixture, unique among the vectors of a layout.
+    pub case: String,
+    /// The expected value as it should look after TypeScript decoding.
+    pub value: Value,
+    /// The borsh-serialized value, or its C memory layout if the layout is
+    /// zero-copy.
+    pub bytes: Vec<u8>,
+    /// Whether the layout is zero-copy.
+    pub zero_copy: bool,
+}
+
+impl GoldenVector {
+    /// File stem of the fixture files belonging to this vector.
+    pub fn file_stem(&self) -> String {
+        format!("{}_{}", self.layout, self.case)
+    }
+}
+
+struct Sampler<'a> {
+    all_layouts: &'a [Layout],
+    layouts: HashMap<&'a str, &'a Layout>,
+}
+
+impl<'a> Sampler<'a> {
+    fn layout(&self, name: &str) -> Result<&'a Layout, anyhow::Error> {
+        self.layouts
+            .get(name)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("no layout found for type `{}`", name))
+    }
+
+    fn sample_layout(
+        &self,
+        layout: &Layout,
+        case: Case,
+        depth: usize,
+        bytes: &mut Vec<u8>,
+    ) -> Result<Value, anyhow::Error> {
+        match layout.kind {
+            Kind::Struct if layout.zero_copy => {
+                // fields of zero-copy layouts are zero-copy themselves and
+                // their borsh encoding matches their memory representation
+                let c_layout = c_layout(layout, self.all_layouts)?;
+                let mut object = Map::new();
+                for (salt, (field, c_field)) in
+                    layout.fields.iter().zip(c_layout.fields.iter()).enumerate()
+                {
+                    bytes.resize(bytes.len() + c_field.padding, 0);
+                    let value = self.sample(field.ty(), case, salt, depth + 1, bytes)?;
+                    object.insert(field.name().to_owned(), value);
+                }
+                bytes.resize(bytes.len() + c_layout.trailing_padding, 0);
+                Ok(Value::Object(object))
+            }
+            Kind::Struct => {
+                let mut object = Map::new();
+                for (salt, field) in layout
+                    .fields
+                    .iter()
+                    .filter(|field| !field.should_skip())
+                    .enumerate()
+                {
+                    let value = self.sample(field.ty(), case, salt, depth + 1, bytes)?;
+                    object.insert(field.name().to_owned(), value);
+                }
+                Ok(Value::Object(object))
+            }
+            Kind::Enum => {
+                let index = match case {
+                    Case::Min => 0,
+                    Case::Max => layout.fields.len().saturating_sub(1),
+                };
+                self.sample_variant(layout, index, case, depth, bytes)
+            }
+        }
+    }
+
+    fn sample_variant(
+        &self,
+        layout: &Layout,
+        index: usize,
+        case: Case,
+        depth: usize,
+        bytes: &mut Vec<u8>,
+    ) -> Result<Value, anyhow::Error> {
+        let variant = layout
+            .fields
+            .get(index)
+            .ok_or_else(|| anyhow::anyhow!("enum `{}` has no variants", layout.name))?;
+        bytes.push(index as u8);
+        let variant_value = self.sample(variant.ty(), case, 0, depth + 1, bytes)?;
+        let mut object = Map::new();
+        object.insert("enum".to_owned(), Value::String(variant.name().to_owned()));
+        object.insert(variant.name().to_owned(), variant_value);
+        Ok(Value::Object(object))
+    }
+
+    fn sample(
+        &self,
+        ty: &BorshType,
+        case: Case,
+        salt: usize,
+        depth: usize,
+        bytes: &mut Vec<u8>,
+    ) -> Result<Value, anyhow::Error> {
+        let case = if depth > MAX_DEPTH { Case::Min } else { case };
+        let value = match ty {
+            BorshType::U8 => sample_integer(u8::MAX as u128, 1, case, salt, bytes),
+            BorshType::U16 => sample_integer(u16::MAX as u128, 2, case, salt, bytes),
+            BorshType::U32 => sample_integer(u32::MAX as u128, 4, case, salt, bytes),
+            BorshType::U64 => sample_integer(u64::MAX as u128, 8, case, salt, bytes),
+            BorshType::U128 => sample_integer(u128::MAX, 16, case, salt, bytes),
+            BorshType::I8 => sample_signed(8, case, salt, bytes),
+            BorshType::I16 => sample_signed(16, case, salt, bytes),
+            BorshType::I32 => sample_signed(32, case, salt, bytes),
+            BorshType::I64 => sample_signed(64, case, salt, bytes),
+            BorshType::I128 => sample_signed(128, case, salt, bytes),
+            BorshType::Bool => {
+                let value = case == Case::Max;
+                bytes.push(value as u8);
+                Value::Bool(value)
+            }
+            BorshType::String => {
+                let value = match (case, salt) {
+                    (Case::Min, 0) => String::new(),
+                    (Case::Min, _) => salt.to_string(),
+                    (Case::Max, _) => format!("golden ✓ {}", salt),
+                };
+                bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+                bytes.extend_from_slice(value.as_bytes());
+                Value::String(value)
+            }
+            BorshType::Pubkey => sample_bytes(32, case, salt, bytes),
+            BorshType::FixedBytes(len) => sample_bytes(*len, case, salt, bytes),
+            BorshType::FixedArray(inner, len) => {
+                let items = (0..*len)
+                    .map(|i| self.sample(inner, case, salt + i, depth + 1, bytes))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::Array(items)
+            }
+            BorshType::Vec(inner) => {
+                let len = if case == Case::Min { 0 } else { COLLECTION_LEN };
+                bytes.extend_from_slice(&(len as u32).to_le_bytes());
+                let items = (0..len)
+                    .map(|i| self.sample(inner, case, salt + i, depth + 1, bytes))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::Array(items)
+            }
+            BorshType::Option(inner) => {
+                if case == Case::Min {
+                    bytes.push(0);
+                    Value::Null
+                } else {
+                    bytes.push(1);
+                    self.sample(inner, case, salt, depth + 1, bytes)?
+                }
+            }
+            BorshType::Map(key, value) => {
+                // keys are sampled with increasing salts so that they are
+                // already in the ascending order borsh expects
+                let len = if case == Case::Min { 0 } else { COLLECTION_LEN };
+                let mut seen_keys = HashSet::new();
+                let mut entries = Vec::new();
+                let mut entry_bytes = Vec::new();
+                for i in 0..len {
+                    let mut key_bytes = Vec::new();
+                    let key_value = self.sample(key, Case::Min, i, depth + 1, &mut key_bytes)?;
+                    if !seen_keys.insert(key_bytes.clone()) {
+                        continue;
+                    }
+                    entry_bytes.append(&mut key_bytes);
+                    let value_value = self.sample(value, case, i, depth + 1, &mut entry_bytes)?;
+                    entries.push(json!([key_value, value_value]));
+                }
+                bytes.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+                bytes.append(&mut entry_bytes);
+                json!({ "$map": entries })
+            }
+            BorshType::Custom(name) => {
+                let layout = self.layout(name)?;
+                self.sample_layout(layout, case, depth, bytes)?
+            }
+            BorshType::Skip => Value::Null,
+        };
+        Ok(value)
+    }
+}
+
+fn sample_integer(max: u128, len: usize, case: Case, salt: usize, bytes: &mut Vec<u8>) -> Value {
+    let salt = salt as u128 % (max / 2);
+    let value = match case {
+        Case::Min => salt,
+        Case::Max => max - salt,
+    };
+    bytes.extend_from_slice(&value.to_le_bytes()[..len]);
+    Value::String(value.to_string())
+}
+
+fn sample_signed(bits: u32, case: Case, salt: usize, bytes: &mut Vec<u8>) -> Value {
+    let max = i128::MAX >> (128 - bits);
+    let salt = salt as i128 % (max / 2);
+    let value = match case {
+        Case::Min => -max - 1 + salt,
+        Case::Max => max - salt,
+    };
+    bytes.extend_from_slice(&value.to_le_bytes()[..bits as usize / 8]);
+    json!({ "$signed": value.to_string(), "bits": bits })
+}
+
+fn sample_bytes(len: usize, case: Case, salt: usize, bytes: &mut Vec<u8>) -> Value {
+    let mut value = match case {
+        Case::Min => vec![0_u8; len],
+        Case::Max => vec![u8::MAX; len],
+    };
+    if let Some(last) = value.last_mut() {
+        match case {
+            Case::Min => *last = salt as u8,
+            Case::Max => *last = u8::MAX - salt as u8,
+        }
+    }
+    bytes.extend_from_slice(&value);
+    json!(value)
+}
+
+/// Generates deterministic sample values for every layout that is not an enum
+/// variant.
+pub fn golden_vectors(layouts: &[Layout]) -> Result<Vec<GoldenVector>, anyhow::Error> {
+    let sampler = Sampler {
+        all_layouts: layouts,
+        layouts: layouts
+            .iter()
+            .map(|layout| (layout.name.as_str(), layout))
+            .collect(),
+    };
+    let variant_names = layouts
+        .iter()
+        .filter(|layout| matches!(layout.kind, Kind::Enum))
+        .flat_map(|layout| layout.fields.iter())
+        .filter_map(|field| match field.ty() {
+            BorshType::Custom(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect::<HashSet<&str>>();
+
+    let mut vectors = Vec::new();
+    for layout in layouts
+        .iter()
+        .filter(|layout| !variant_names.contains(layout.name.as_str()))
+    {
+        match layout.kind {
+            Kind::Struct => {
+                for (case, case_name) in [(Case::Min, "min"), (Case::Max, "max")] {
+                    let mut bytes = Vec::new();
+                    let value = sampler.sample_layout(layout, case, 0, &mut bytes)?;
+                    vectors.push(GoldenVector {
+                        layout: layout.name.clone(),
+                        case: case_name.to_owned(),
+                        value,
+                        bytes,
+                        zero_copy: layout.zero_copy,
+                    });
+                }
+            }
+            Kind::Enum => {
+                for (index, variant) in layout.fields.iter().enumerate() {
+                    let mut bytes = Vec::new();
+                    let value = sampler.sample_variant(layout, index, Case::Max, 0, &mut bytes)?;
+                    vectors.push(GoldenVector {
+                        layout: layout.name.clone(),
+                        case: variant.name().to_owned(),
+                        value,
+                        bytes,
+                        zero_copy: false,
+                    });
+                }
+            }
+        }
+    }
+    Ok(vectors)
+}
+
+/// Writes JSON and borsh hex fixtures of every layout into the `golden`
+/// subdirectory of the output directory along with a `golden.test.ts` file
+/// that decodes each fixture with the generated schema (or zero-copy
+/// decoder).
+pub fn generate_golden_vectors(
+    layouts: &[Layout],
+    output_directory: impl AsRef<Path>,
+) -> Result<(), anyhow::Error> {
+    let vectors = golden_vectors(layouts)?;
+    let golden_directory = output_directory.as_ref().join(GOLDEN_DIRECTORY);
+    fs::create_dir_all(&golden_directory)?;
+
+    for vector in &vectors {
+        let stem = vector.file_stem();
+        let mut file = fs::File::create(golden_directory.join(stem.clone() + ".json"))?;
+        writeln!(file, "{}", serde_json::to_string(&vector.value)?)?;
+        let hex = vector
+            .bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        let mut file = fs::File::create(golden_directory.join(stem + ".hex"))?;
+        writeln!(file, "{}", hex)?;
+    }
+
+    let mut file = fs::File::create(output_directory.as_ref().join("golden.test.ts"))?;
+    write!(file, "{}", golden_test_ts(&vectors))?;
+    Ok(())
+}
+
+fn golden_test_ts(vectors: &[GoldenVector]) -> String {
+    let decoder = |vector: &GoldenVector| {
+        if vector.zero_copy {
+            format!("decode{}", vector.layout)
+        } else {
+            "null".to_owned()
+        }
+    };
+    let fixtures = vectors
+        .iter()
+        .map(|vector| {
+            format!(
+                "\n    [{}, \"{}\", {}],",
+                vector.layout,
+                vector.file_stem(),
+                decoder(vector)
+            )
+        })
+        .collect::<String>();
+    let imports = vectors
+        .iter()
+        .map(|vector| vector.layout.clone())
+        .chain(
+            vectors
+                .iter()
+                .filter(|vector| vector.zero_copy)
+                .map(decoder),
+        )
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .map(|name| format!("\n    {},", name))
+        .collect::<String>();
+    format!(
+        r#"import {{{}
+    SCHEMA
+}} from "./schema";
+import {{ PublicKey }} from "@solana/web3.js";
+import {{ serialize, deserializeUnchecked }} from "borsh";
+import BN from "bn.js";
+
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+
+const FIXTURES: [any, string, ((data: Buffer) => any) | null][] = [{}
+];
+
+function assertGolden(actual: any, expected: any, at: string) {{
+    if (expected === null) {{
+        assert(actual === null || actual === undefined, at);
+    }} else if (typeof expected === "boolean") {{
+        assert(Number(actual) === Number(expected), at);
+    }} else if (typeof expected === "string") {{
+        assert(actual.toString() === expected, at);
+    }} else if (Array.isArray(expected)) {{
+        const items = actual instanceof PublicKey ? Array.from(actual.toBytes()) : Array.from(actual);
+        assert(items.length === expected.length, at);
+        expected.forEach((item, i) => assertGolden(items[i], item, `${{at}}[${{i}}]`));
+    }} else if ("$signed" in expected) {{
+        // borsh-js decodes signed integers as their unsigned two's complement
+        const value = new BN(actual.toString());
+        const signed = value.isNeg() ? value : value.fromTwos(expected.bits);
+        assert(signed.toString() === expected.$signed, at);
+    }} else if ("$map" in expected) {{
+        const entries = Array.from(actual.entries());
+        assert(entries.length === expected.$map.length, at);
+        expected.$map.forEach(([key, value], i) => {{
+            assertGolden(entries[i][0], key, `${{at}}.key[${{i}}]`);
+            assertGolden(entries[i][1], value, `${{at}}.value[${{i}}]`);
+        }});
+    }} else {{
+        Object.keys(expected).forEach((key) => assertGolden(actual[key], expected[key], `${{at}}.${{key}}`));
+    }}
+}}
+
+for (const [layout, fixture, decode] of FIXTURES) {{
+    const fixturePath = path.join(__dirname, "golden", fixture);
+    const expected = JSON.parse(fs.readFileSync(fixturePath + ".json").toString());
+    const bytes = Buffer.from(fs.readFileSync(fixturePath + ".hex").toString().trim(), "hex");
+
+    if (decode !== null) {{
+        // zero-copy fixtures contain the C memory layout
+        assertGolden(decode(bytes), expected, fixture);
+        continue;
+    }}
+
+    const decoded = deserializeUnchecked(SCHEMA, layout, bytes);
+    assertGolden(decoded, expected, fixture);
+
+    const encoded = Buffer.from(serialize(SCHEMA, decoded));
+    assert(encoded.equals(bytes), fixture);
+}}
+"#,
+        imports, fixtures
+    )
+}
@@ -9,6 +9,11 @@ pub enum BorshType {
     U32,
     U64,
     U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
     Bool,
     String,
     Pubkey,
@@ -27,11 +32,16 @@ impl FromStr for BorshType {
         let mut input = input_str.to_owned();
         input.retain(|c| !c.is_whitespace());
         match input.as_ref() {
-            "u8" | "i8" => Ok(BorshType::U8),
-            "u16" | "i16" => Ok(BorshType::U16),
-            "u32" | "i32" => Ok(BorshType::U32),
-            "u64" | "i64" | "UnixTimestamp" => Ok(BorshType::U64),
-            "u128" | "i128" => Ok(BorshType::U128),
+            "u8" => Ok(BorshType::U8),
+            "u16" => Ok(BorshType::U16),
+            "u32" => Ok(BorshType::U32),
+            "u64" => Ok(BorshType::U64),
+            "u128" => Ok(BorshType::U128),
+            "i8" => Ok(BorshType::I8),
+            "i16" => Ok(BorshType::I16),
+            "i32" => Ok(BorshType::I32),
+            "i64" | "UnixTimestamp" => Ok(BorshType::I64),
+            "i128" => Ok(BorshType::I128),
             "bool" => Ok(BorshType::Bool),
             "String" => Ok(BorshType::String),
             "Pubkey" => Ok(BorshType::Pubkey),
@@ -63,7 +73,7 @@ impl FromStr for BorshType {
                         .ok_or_else(|| anyhow::anyhow!("invalid array, missing ';'"))?;
                     let array_type = BorshType::from_str(array_type_str)?;
                     let array_len = array_len_str.parse::<usize>()?;
-                    if let BorshType::U8 = array_type {
+                    if let BorshType::U8 | BorshType::I8 = array_type {
                         Ok(BorshType::FixedBytes(array_len))
                     } else {
                         Ok(BorshType::FixedArray(Box::new(array_type), array_len))
@@ -89,13 +99,16 @@ impl FromStr for BorshType {
 impl BorshType {
     /// Converts the type to a schema representation used by
     /// [`borsh-js`](https://github.com/near/borsh-js).
+    ///
+    /// `borsh-js` has no signed integers, so they are decoded as the two's
+    /// complement of their unsigned counterpart (see `BN::fromTwos`).
     pub fn to_borsh_schema(&self) -> String {
         match self {
-            Self::U8 | Self::Bool => "'u8'".to_owned(),
-            Self::U16 => "'u16'".to_owned(),
-            Self::U32 => "'u32'".to_owned(),
-            Self::U64 => "'u64'".to_owned(),
-            Self::U128 => "'u128'".to_owned(),
+            Self::U8 | Self::I8 | Self::Bool => "'u8'".to_owned(),
+            Self::U16 | Self::I16 => "'u16'".to_owned(),
+            Self::U32 | Self::I32 => "'u32'".to_owned(),
+            Self::U64 | Self::I64 => "'u64'".to_owned(),
+            Self::U128 | Self::I128 => "'u128'".to_owned(),
             Self::String => "'string'".to_owned(),
             Self::Pubkey => "'publicKey'".to_owned(),
             Self::Vec(inner) => format!("[{}]", inner.to_borsh_schema()),
@@ -117,11 +130,11 @@ impl BorshType {
     /// Converts the type to a TypeScript class type.
     pub fn to_class_type(&self) -> String {
         match self {
-            Self::U8 => "number".to_owned(),
-            Self::U16 => "number".to_owned(),
-            Self::U32 => "number".to_owned(),
-            Self::U64 => "BN".to_owned(),
-            Self::U128 => "BN".to_owned(),
+            Self::U8 | Self::I8 => "number".to_owned(),
+            Self::U16 | Self::I16 => "number".to_owned(),
+            Self::U32 | Self::I32 => "number".to_owned(),
+            Self::U64 | Self::I64 => "BN".to_owned(),
+            Self::U128 | Self::I128 => "BN".to_owned(),
             Self::Bool => "boolean".to_owned(),
             Self::String => "string".to_owned(),
             Self::Pubkey => "PublicKey".to_owned(),
@@ -148,15 +161,19 @@ mod test {
     fn simple_type_from_str() {
         assert_eq!(BorshType::from_str("bool").unwrap(), BorshType::Bool);
         assert_eq!(BorshType::from_str("u8").unwrap(), BorshType::U8);
-        assert_eq!(BorshType::from_str("i8").unwrap(), BorshType::U8);
+        assert_eq!(BorshType::from_str("i8").unwrap(), BorshType::I8);
         assert_eq!(BorshType::from_str("u16").unwrap(), BorshType::U16);
-        assert_eq!(BorshType::from_str("i16").unwrap(), BorshType::U16);
+        assert_eq!(BorshType::from_str("i16").unwrap(), BorshType::I16);
         assert_eq!(BorshType::from_str("u32").unwrap(), BorshType::U32);
-        assert_eq!(BorshType::from_str("i32").unwrap(), BorshType::U32);
+        assert_eq!(BorshType::from_str("i32").unwrap(), BorshType::I32);
         assert_eq!(BorshType::from_str("u64").unwrap(), BorshType::U64);
-        assert_eq!(BorshType::from_str("i64").unwrap(), BorshType::U64);
+        assert_eq!(BorshType::from_str("i64").unwrap(), BorshType::I64);
+        assert_eq!(
+            BorshType::from_str("UnixTimestamp").unwrap(),
+            BorshType::I64
+        );
         assert_eq!(BorshType::from_str("u128").unwrap(), BorshType::U128);
-        assert_eq!(BorshType::from_str("i128").unwrap(), BorshType::U128);
+        assert_eq!(BorshType::from_str("i128").unwrap(), BorshType::I128);
         assert_eq!(BorshType::from_str("String").unwrap(), BorshType::String);
         assert_eq!(BorshType::from_str("Pubkey").unwrap(), BorshType::Pubkey);
         assert_eq!(
@@ -189,7 +206,7 @@ mod test {
             BorshType::from_str("[[Option<i32>; 2]; 4]").unwrap(),
             BorshType::FixedArray(
                 Box::new(BorshType::FixedArray(
-                    Box::new(BorshType::Option(Box::new(BorshType::U32))),
+                    Box::new(BorshType::Option(Box::new(BorshType::I32))),
                     2
                 )),
                 4
@@ -217,6 +234,7 @@ mod test {
         assert_eq!(BorshType::U32.to_borsh_schema(), "'u32'");
         assert_eq!(BorshType::U64.to_borsh_schema(), "'u64'");
         assert_eq!(BorshType::U128.to_borsh_schema(), "'u128'");
+        assert_eq!(BorshType::I64.to_borsh_schema(), "'u64'");
         assert_eq!(BorshType::String.to_borsh_schema(), "'string'");
         assert_eq!(BorshType::Pubkey.to_borsh_schema(), "'publicKey'");
     }
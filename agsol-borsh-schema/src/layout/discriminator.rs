@@ -0,0 +1,30 @@
+use super::Layout;
+use heck::ShoutySnakeCase;
+
+/// Generates a TypeScript helper that verifies and strips the discriminator
+/// before decoding the account data.
+pub fn to_ts_discriminator_helper(layout: &Layout, discriminator: &[u8]) -> String {
+    let constant = format!("{}_DISCRIMINATOR", layout.name.to_shouty_snake_case());
+    let len = discriminator.len();
+    let decode = if layout.zero_copy {
+        format!("decode{}(data, {})", layout.name, len)
+    } else {
+        format!(
+            "deserializeUnchecked(SCHEMA, {}, data.subarray({}))",
+            layout.name, len
+        )
+    };
+    format!(
+        r#"export const {0} = Buffer.from({1:?});
+
+export function decode{2}Account(data: Buffer): {2} {{
+    if (data.length < {3} || !data.subarray(0, {3}).equals({0})) {{
+        throw new Error("invalid {2} account discriminator");
+    }}
+    return {4};
+}}
+
+"#,
+        constant, discriminator, layout.name, len, decode
+    )
+}
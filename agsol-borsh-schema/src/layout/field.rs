@@ -43,6 +43,14 @@ impl LayoutField {
         })
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ty(&self) -> &BorshType {
+        &self.ty
+    }
+
     /// Converts the field into a TypeScript class field representation.
     pub fn to_class_field(&self) -> String {
         format!("{}: {}", self.name, self.ty.to_class_type())
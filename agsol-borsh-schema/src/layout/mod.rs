@@ -1,7 +1,12 @@
 mod borsh_type;
+mod discriminator;
 mod field;
+mod seed;
+mod zero_copy;
 pub use borsh_type::BorshType;
 pub use field::LayoutField;
+pub use seed::Seed;
+pub use zero_copy::{c_layout, CField, CLayout};
 
 /// Indicates whether the layout should be generated for a `struct` or an
 /// `enum` type.
@@ -18,6 +23,13 @@ pub struct Layout {
     pub name: String,
     pub kind: Kind,
     pub fields: Vec<LayoutField>,
+    pub seeds: Vec<Seed>,
+    /// Whether the layout describes a `#[repr(C)]` data structure that is
+    /// read directly from memory instead of being borsh-decoded.
+    pub zero_copy: bool,
+    /// Bytes prepended to the serialized account data, e.g. an Anchor
+    /// discriminator.
+    pub discriminator: Option<Vec<u8>>,
 }
 
 impl Layout {
@@ -34,6 +46,9 @@ impl Layout {
             name: name.to_string(),
             kind: Kind::Struct,
             fields,
+            seeds: Vec::new(),
+            zero_copy: false,
+            discriminator: None,
         })
     }
 
@@ -54,6 +69,37 @@ impl Layout {
         )
     }
 
+    /// Converts the layout's seed recipe (if any) into a TypeScript PDA
+    /// derivation helper.
+    pub fn to_ts_pda_helper(&self) -> Option<String> {
+        if self.seeds.is_empty() {
+            None
+        } else {
+            Some(seed::to_ts_pda_helper(&self.name, &self.seeds))
+        }
+    }
+
+    /// Converts a zero-copy layout into a TypeScript offsets table and decoder
+    /// function.
+    pub fn to_ts_zero_copy_decoder(
+        &self,
+        layouts: &[Layout],
+    ) -> Result<Option<String>, anyhow::Error> {
+        if self.zero_copy {
+            zero_copy::to_ts_zero_copy_decoder(self, layouts).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Converts the layout's discriminator (if any) into a TypeScript constant
+    /// and a `decode<Name>Account` helper that verifies and strips it.
+    pub fn to_ts_discriminator_helper(&self) -> Option<String> {
+        self.discriminator
+            .as_ref()
+            .map(|discriminator| discriminator::to_ts_discriminator_helper(self, discriminator))
+    }
+
     /// Converts the layout into a borsh schema.
     pub fn to_borsh_schema(&self) -> String {
         let first_line = match self.kind {
@@ -0,0 +1,222 @@
+use super::BorshType;
+use agsol_borsh_schema_parse as parse;
+use heck::MixedCase;
+
+use std::str::FromStr;
+
+/// A single component of a PDA seed recipe declared via the `seeds`
+/// attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Seed {
+    /// Literal bytes, e.g. `b"pool"` or `"pool"`.
+    Literal(Vec<u8>),
+    /// A public key parameter, e.g. `authority: Pubkey`.
+    Pubkey(String),
+    /// An integer parameter encoded with the given endianness, e.g.
+    /// `index: u64` (little endian) or `index: u64(be)`.
+    Integer {
+        name: String,
+        ty: BorshType,
+        big_endian: bool,
+    },
+}
+
+impl Seed {
+    /// Parses the seed recipe from a `#[seeds(...)]` attribute.
+    pub fn from_attribute(attr: &syn::Attribute) -> Result<Vec<Self>, anyhow::Error> {
+        let seeds = parse::Seed::from_attribute(attr)?;
+        seeds
+            .into_iter()
+            .map(|seed| match seed {
+                parse::Seed::Literal(bytes) => Ok(Self::Literal(bytes)),
+                parse::Seed::Pubkey(name) => Ok(Self::Pubkey(name.to_string())),
+                parse::Seed::Integer {
+                    name,
+                    ty,
+                    big_endian,
+                } => Ok(Self::Integer {
+                    name: name.to_string(),
+                    ty: BorshType::from_str(&ty.to_string())
+                        .map_err(|e| syn::Error::new(ty.span(), e))?,
+                    big_endian,
+                }),
+            })
+            .collect()
+    }
+
+    /// Returns the TypeScript parameter this seed requires, if any.
+    pub fn to_ts_param(&self) -> Option<String> {
+        match self {
+            Self::Literal(_) => None,
+            Self::Pubkey(name) => Some(format!("{}: PublicKey", name.to_mixed_case())),
+            Self::Integer { name, ty, .. } => {
+                Some(format!("{}: {}", name.to_mixed_case(), ty.to_class_type()))
+            }
+        }
+    }
+
+    /// Converts the seed into a TypeScript expression evaluating to a
+    /// `Buffer`.
+    pub fn to_ts_buffer(&self) -> String {
+        match self {
+            Self::Literal(bytes) => {
+                if bytes
+                    .iter()
+                    .all(|b| (b.is_ascii_graphic() && *b != b'"' && *b != b'\\') || *b == b' ')
+                {
+                    format!("Buffer.from(\"{}\")", String::from_utf8_lossy(bytes))
+                } else {
+                    format!("Buffer.from({:?})", bytes)
+                }
+            }
+            Self::Pubkey(name) => format!("{}.toBuffer()", name.to_mixed_case()),
+            Self::Integer {
+                name,
+                ty,
+                big_endian,
+            } => {
+                let len = match ty {
+                    BorshType::U8 | BorshType::I8 => 1,
+                    BorshType::U16 | BorshType::I16 => 2,
+                    BorshType::U32 | BorshType::I32 => 4,
+                    BorshType::U64 | BorshType::I64 => 8,
+                    _ => 16,
+                };
+                let endianness = if *big_endian { "be" } else { "le" };
+                format!(
+                    "new BN({}).toTwos({}).toArrayLike(Buffer, \"{}\", {})",
+                    name.to_mixed_case(),
+                    len * 8,
+                    endianness,
+                    len
+                )
+            }
+        }
+    }
+}
+
+/// Generates a TypeScript `find<Name>Address` helper from a seed recipe.
+pub fn to_ts_pda_helper(name: &str, seeds: &[Seed]) -> String {
+    let params = seeds
+        .iter()
+        .filter_map(|seed| seed.to_ts_param())
+        .map(|param| format!("\n    {},", param))
+        .collect::<String>();
+    let buffers = seeds
+        .iter()
+        .map(|seed| format!("\n            {},", seed.to_ts_buffer()))
+        .collect::<String>();
+    format!(
+        r#"export async function find{}Address(
+    programId: PublicKey,{}
+): Promise<[PublicKey, number]> {{
+    return await PublicKey.findProgramAddress(
+        [{}
+        ],
+        programId
+    );
+}}
+
+"#,
+        name, params, buffers
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_seed_attribute() {
+        let attr: syn::Attribute = syn::parse_quote! {
+            #[seeds(b"pool", "x", authority: Pubkey, pool_index: u64, epoch: u16(be))]
+        };
+        let seeds = Seed::from_attribute(&attr).unwrap();
+        assert_eq!(
+            seeds,
+            vec![
+                Seed::Literal(b"pool".to_vec()),
+                Seed::Literal(b"x".to_vec()),
+                Seed::Pubkey("authority".to_owned()),
+                Seed::Integer {
+                    name: "pool_index".to_owned(),
+                    ty: BorshType::U64,
+                    big_endian: false,
+                },
+                Seed::Integer {
+                    name: "epoch".to_owned(),
+                    ty: BorshType::U16,
+                    big_endian: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_seed_attribute() {
+        let attr: syn::Attribute = syn::parse_quote! { #[seeds(name: String)] };
+        assert!(Seed::from_attribute(&attr).is_err());
+        let attr: syn::Attribute = syn::parse_quote! { #[seeds(index: u8(middle))] };
+        assert!(Seed::from_attribute(&attr).is_err());
+        let attr: syn::Attribute = syn::parse_quote! { #[seeds(owner: Pubkey(be))] };
+        assert!(Seed::from_attribute(&attr).is_err());
+        // accepted by `BorshType`, but not as a seed
+        let attr: syn::Attribute = syn::parse_quote! { #[seeds(time: UnixTimestamp)] };
+        assert!(Seed::from_attribute(&attr).is_err());
+    }
+
+    #[test]
+    fn seeds_to_ts() {
+        assert_eq!(
+            Seed::Literal(b"pool".to_vec()).to_ts_buffer(),
+            "Buffer.from(\"pool\")"
+        );
+        assert_eq!(
+            Seed::Literal(vec![0, 255]).to_ts_buffer(),
+            "Buffer.from([0, 255])"
+        );
+        let seed = Seed::Pubkey("pool_authority".to_owned());
+        assert_eq!(seed.to_ts_buffer(), "poolAuthority.toBuffer()");
+        assert_eq!(seed.to_ts_param().unwrap(), "poolAuthority: PublicKey");
+        let seed = Seed::Integer {
+            name: "index".to_owned(),
+            ty: BorshType::U32,
+            big_endian: true,
+        };
+        assert_eq!(
+            seed.to_ts_buffer(),
+            "new BN(index).toTwos(32).toArrayLike(Buffer, \"be\", 4)"
+        );
+        assert_eq!(seed.to_ts_param().unwrap(), "index: number");
+    }
+
+    #[test]
+    fn signed_seeds() {
+        let attr: syn::Attribute = syn::parse_quote! { #[seeds(delta: i64, offset: i16(be))] };
+        let seeds = Seed::from_attribute(&attr).unwrap();
+        assert_eq!(
+            seeds,
+            vec![
+                Seed::Integer {
+                    name: "delta".to_owned(),
+                    ty: BorshType::I64,
+                    big_endian: false,
+                },
+                Seed::Integer {
+                    name: "offset".to_owned(),
+                    ty: BorshType::I16,
+                    big_endian: true,
+                },
+            ]
+        );
+        // negative values are converted into their two's complement
+        assert_eq!(
+            seeds[0].to_ts_buffer(),
+            "new BN(delta).toTwos(64).toArrayLike(Buffer, \"le\", 8)"
+        );
+        assert_eq!(
+            seeds[1].to_ts_buffer(),
+            "new BN(offset).toTwos(16).toArrayLike(Buffer, \"be\", 2)"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use super::{BorshType, Kind, Layout};
+use heck::ShoutySnakeCase;
+
+/// Position of a field within a `#[repr(C)]` data structure.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CField {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+    /// Padding bytes inserted before the field to satisfy its alignment.
+    pub padding: usize,
+}
+
+/// The C memory layout of a `#[repr(C)]` data structure.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CLayout {
+    pub size: usize,
+    pub align: usize,
+    pub fields: Vec<CField>,
+    /// Padding bytes at the end of the data structure.
+    pub trailing_padding: usize,
+}
+
+fn align_up(offset: usize, align: usize) -> usize {
+    offset + (align - offset % align) % align
+}
+
+fn find_layout<'a>(name: &str, layouts: &'a [Layout]) -> Result<&'a Layout, anyhow::Error> {
+    layouts
+        .iter()
+        .find(|layout| layout.name == name)
+        .ok_or_else(|| anyhow::anyhow!("no layout found for type `{}`", name))
+}
+
+/// Returns the size and alignment of a POD-compatible type on the Solana
+/// runtime.
+///
+/// Note that `u128` is only 8-aligned on-chain (unlike on e.g. `x86_64`).
+fn size_and_align(ty: &BorshType, layouts: &[Layout]) -> Result<(usize, usize), anyhow::Error> {
+    match ty {
+        BorshType::U8 | BorshType::I8 => Ok((1, 1)),
+        BorshType::U16 | BorshType::I16 => Ok((2, 2)),
+        BorshType::U32 | BorshType::I32 => Ok((4, 4)),
+        BorshType::U64 | BorshType::I64 => Ok((8, 8)),
+        BorshType::U128 | BorshType::I128 => Ok((16, 8)),
+        BorshType::Pubkey => Ok((32, 1)),
+        BorshType::FixedBytes(len) => Ok((*len, 1)),
+        BorshType::FixedArray(inner, len) => {
+            let (size, align) = size_and_align(inner, layouts)?;
+            Ok((size * len, align))
+        }
+        BorshType::Custom(name) => {
+            let c_layout = c_layout(find_layout(name, layouts)?, layouts)?;
+            Ok((c_layout.size, c_layout.align))
+        }
+        _ => Err(anyhow::anyhow!("{:?} is not POD-compatible", ty)),
+    }
+}
+
+/// Computes the C memory layout of a layout annotated with
+/// `#[schema(zero_copy)]`.
+pub fn c_layout(layout: &Layout, layouts: &[Layout]) -> Result<CLayout, anyhow::Error> {
+    if !layout.zero_copy {
+        anyhow::bail!(
+            "`{}` is not annotated with `#[schema(zero_copy)]`",
+            layout.name
+        );
+    }
+    if let Kind::Enum = layout.kind {
+        anyhow::bail!("enum `{}` is not POD-compatible", layout.name);
+    }
+
+    let mut offset = 0;
+    let mut align = 1;
+    let mut fields = Vec::with_capacity(layout.fields.len());
+    for field in &layout.fields {
+        if field.should_skip() {
+            anyhow::bail!(
+                "field `{}` of `{}` cannot be skipped in a zero-copy layout",
+                field.name(),
+                layout.name
+            );
+        }
+        let (size, field_align) = size_and_align(field.ty(), layouts)
+            .map_err(|e| anyhow::anyhow!("field `{}` of `{}`: {}", field.name(), layout.name, e))?;
+        let field_offset = align_up(offset, field_align);
+        fields.push(CField {
+            name: field.name().to_owned(),
+            offset: field_offset,
+            size,
+            padding: field_offset - offset,
+        });
+        offset = field_offset + size;
+        align = align.max(field_align);
+    }
+    let size = align_up(offset, align);
+    Ok(CLayout {
+        size,
+        align,
+        fields,
+        trailing_padding: size - offset,
+    })
+}
+
+/// Returns a TypeScript expression that reads a value of the given type from
+/// `data` at the given offset expression.
+fn to_ts_read(
+    ty: &BorshType,
+    offset: &str,
+    depth: usize,
+    layouts: &[Layout],
+) -> Result<String, anyhow::Error> {
+    let read = match ty {
+        BorshType::U8 => format!("data.readUInt8({})", offset),
+        BorshType::U16 => format!("data.readUInt16LE({})", offset),
+        BorshType::U32 => format!("data.readUInt32LE({})", offset),
+        BorshType::U64 => format!("new BN(data.subarray({0}, {0} + 8), \"le\")", offset),
+        BorshType::U128 => format!("new BN(data.subarray({0}, {0} + 16), \"le\")", offset),
+        BorshType::I8 => format!("data.readInt8({})", offset),
+        BorshType::I16 => format!("data.readInt16LE({})", offset),
+        BorshType::I32 => format!("data.readInt32LE({})", offset),
+        BorshType::I64 => format!(
+            "new BN(data.subarray({0}, {0} + 8), \"le\").fromTwos(64)",
+            offset
+        ),
+        BorshType::I128 => format!(
+            "new BN(data.subarray({0}, {0} + 16), \"le\").fromTwos(128)",
+            offset
+        ),
+        BorshType::Pubkey => format!("new PublicKey(data.subarray({0}, {0} + 32))", offset),
+        BorshType::FixedBytes(len) => format!("data.slice({0}, {0} + {1})", offset, len),
+        BorshType::FixedArray(inner, len) => {
+            let (size, _) = size_and_align(inner, layouts)?;
+            let index = format!("i{}", depth);
+            let inner_offset = format!("{} + {} * {}", offset, index, size);
+            format!(
+                "Array.from({{ length: {} }}, (_, {}) => {})",
+                len,
+                index,
+                to_ts_read(inner, &inner_offset, depth + 1, layouts)?
+            )
+        }
+        BorshType::Custom(name) => format!("decode{}(data, {})", name, offset),
+        _ => anyhow::bail!("{:?} is not POD-compatible", ty),
+    };
+    Ok(read)
+}
+
+/// Generates a TypeScript offsets table and a `decode<Name>` function that
+/// reads the data structure directly from its C memory layout.
+pub fn to_ts_zero_copy_decoder(
+    layout: &Layout,
+    layouts: &[Layout],
+) -> Result<String, anyhow::Error> {
+    let c_layout = c_layout(layout, layouts)?;
+    let offsets = c_layout
+        .fields
+        .iter()
+        .map(|field| format!("\n        {}: {},", field.name, field.offset))
+        .collect::<String>();
+    let reads = layout
+        .fields
+        .iter()
+        .zip(c_layout.fields.iter())
+        .map(|(field, c_field)| {
+            let offset = format!("offset + {}", c_field.offset);
+            to_ts_read(field.ty(), &offset, 0, layouts)
+                .map(|read| format!("\n        {}: {},", c_field.name, read))
+        })
+        .collect::<Result<String, _>>()?;
+    Ok(format!(
+        r#"export const {}_LAYOUT = {{
+    span: {},
+    align: {},
+    offsets: {{{}
+    }},
+}};
+
+export function decode{}(data: Buffer, offset: number = 0): {} {{
+    return new {}({{{}
+    }});
+}}
+
+"#,
+        layout.name.to_shouty_snake_case(),
+        c_layout.size,
+        c_layout.align,
+        offsets,
+        layout.name,
+        layout.name,
+        layout.name,
+        reads,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn zero_copy_layout(name: &str, item: syn::ItemStruct) -> Layout {
+        let mut layout = Layout::from_tokens(name, &mut item.fields.iter()).unwrap();
+        layout.zero_copy = true;
+        layout
+    }
+
+    #[test]
+    fn c_layout_with_padding() {
+        let inner = zero_copy_layout(
+            "Inner",
+            syn::parse_quote! {
+                struct Inner {
+                    a: u16,
+                    b: u8,
+                }
+            },
+        );
+        let outer = zero_copy_layout(
+            "Outer",
+            syn::parse_quote! {
+                struct Outer {
+                    flag: u8,
+                    amount: u64,
+                    owner: Pubkey,
+                    inner: [Inner; 2],
+                    last: u32,
+                }
+            },
+        );
+        let layouts = vec![inner, outer];
+
+        let inner_layout = c_layout(&layouts[0], &layouts).unwrap();
+        assert_eq!(inner_layout.size, 4);
+        assert_eq!(inner_layout.align, 2);
+        assert_eq!(inner_layout.trailing_padding, 1);
+
+        let outer_layout = c_layout(&layouts[1], &layouts).unwrap();
+        let offsets = outer_layout
+            .fields
+            .iter()
+            .map(|field| (field.offset, field.padding))
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![(0, 0), (8, 7), (16, 0), (48, 0), (56, 0)]);
+        assert_eq!(outer_layout.size, 64);
+        assert_eq!(outer_layout.align, 8);
+        assert_eq!(outer_layout.trailing_padding, 4);
+    }
+
+    #[test]
+    fn u128_alignment() {
+        let layouts = vec![zero_copy_layout(
+            "Wide",
+            syn::parse_quote! {
+                struct Wide {
+                    flag: u8,
+                    amount: u64,
+                    total: u128,
+                    last: u32,
+                }
+            },
+        )];
+        let layout = c_layout(&layouts[0], &layouts).unwrap();
+        let offsets = layout
+            .fields
+            .iter()
+            .map(|field| field.offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![0, 8, 16, 32]);
+        assert_eq!(layout.size, 40);
+        assert_eq!(layout.align, 8);
+    }
+
+    #[test]
+    fn signed_reads() {
+        let layouts = Vec::new();
+        let read = |ty: &str| to_ts_read(&ty.parse().unwrap(), "8", 0, &layouts).unwrap();
+        assert_eq!(read("u32"), "data.readUInt32LE(8)");
+        assert_eq!(read("i8"), "data.readInt8(8)");
+        assert_eq!(read("i32"), "data.readInt32LE(8)");
+        assert_eq!(
+            read("i64"),
+            "new BN(data.subarray(8, 8 + 8), \"le\").fromTwos(64)"
+        );
+        assert_eq!(
+            read("i128"),
+            "new BN(data.subarray(8, 8 + 16), \"le\").fromTwos(128)"
+        );
+    }
+
+    #[test]
+    fn non_pod_fields() {
+        for item in [
+            syn::parse_quote! { struct Foo { a: Vec<u8> } },
+            syn::parse_quote! { struct Foo { a: Option<u64> } },
+            syn::parse_quote! { struct Foo { a: String } },
+            syn::parse_quote! { struct Foo { a: bool } },
+            syn::parse_quote! { struct Foo { a: [BTreeMap<u8, u8>; 2] } },
+            syn::parse_quote! { struct Foo { a: NotZeroCopy } },
+        ] {
+            let layouts = vec![
+                zero_copy_layout("Foo", item),
+                Layout::from_tokens("NotZeroCopy", &mut std::iter::empty()).unwrap(),
+            ];
+            assert!(c_layout(&layouts[0], &layouts).is_err());
+        }
+    }
+}
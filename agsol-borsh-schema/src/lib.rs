@@ -23,6 +23,14 @@
 
 pub use agsol_borsh_schema_derive::*;
 
+// lets the paths generated by the derive resolve within this crate
+#[cfg(test)]
+extern crate self as agsol_borsh_schema;
+
+#[cfg(feature = "full")]
+mod build;
+#[cfg(feature = "full")]
+mod golden;
 /// Intermediate data structures used for generating
 /// schema an TypeScript class layouts.
 #[cfg(feature = "full")]
@@ -32,6 +40,14 @@ mod test;
 #[cfg(feature = "full")]
 mod utils;
 
+#[cfg(feature = "full")]
+pub use agsol_borsh_schema_parse::anchor_discriminator;
+#[cfg(feature = "full")]
+pub use build::{build_schema, build_schema_from, LayoutError};
+#[cfg(feature = "full")]
+pub use golden::{generate_golden_vectors, golden_vectors, GoldenVector};
+#[cfg(feature = "full")]
+pub use layout::{c_layout, CField, CLayout};
 #[cfg(feature = "full")]
 pub use utils::*;
 
@@ -74,4 +90,103 @@ pub use utils::*;
 /// doesn't know that `SomeAlias` is actually a byte array without the `alias`
 /// attribute. If the `alias` attribute is omitted, the generated TypeScript
 /// code will contain `SomeAlias` instead of `Uint8Array`.
+///
+/// # PDA seeds
+/// The `seeds` attribute declares the seed recipe of a program derived
+/// address belonging to the data structure. A seed is either a literal
+/// (`b"pool"` or `"pool"`), a `Pubkey` parameter or an integer parameter that
+/// is encoded in little endian by default and in big endian with a `(be)`
+/// suffix.
+///
+/// ```rust
+/// use agsol_borsh_schema::BorshSchema;
+/// use solana_program::pubkey::Pubkey;
+///
+/// #[derive(BorshSchema)]
+/// #[seeds(b"pool", authority: Pubkey, index: u64, epoch: u16(be))]
+/// struct Pool {
+///     authority: Pubkey,
+///     index: u64,
+/// }
+///
+/// let program_id = Pubkey::new_unique();
+/// let authority = Pubkey::new_unique();
+/// let (pda, _bump) = Pool::find_address(&program_id, &authority, 3, 10);
+/// let seeds = Pool::seeds(&authority, 3, 10);
+/// assert_eq!(seeds[0], Pool::SEED_PREFIX);
+/// ```
+///
+/// The derive generates the `seeds` and `find_address` associated functions
+/// (and a `SEED_PREFIX` constant if the recipe starts with a literal), while
+/// the parser generates an equivalent
+/// `findPoolAddress(programId, authority, index, epoch)` TypeScript helper.
+///
+/// The generated functions refer to `::solana_program`, so the crate deriving
+/// `BorshSchema` with seeds must depend on `solana-program` directly (a
+/// re-export through another crate is not enough). Literal seeds are limited
+/// to 32 bytes and a recipe to 15 seeds, which is checked at compile time.
+///
+/// # Zero-copy layouts
+/// `#[repr(C)]` structs that are read directly from account data (e.g.
+/// `bytemuck::Pod` types) can be annotated with `#[schema(zero_copy)]`. The
+/// parser then computes the C layout (offsets, alignment and padding) of the
+/// struct and generates a `<NAME>_LAYOUT` offsets table along with a
+/// `decode<Name>` TypeScript function. Fields must be integers, `Pubkey`s,
+/// arrays of them or other zero-copy layouts (i.e. types implementing
+/// [`ZeroCopySchema`]), anything else (`bool`, `String`, `Vec`, `Option`,
+/// maps, etc.) results in an error. Signed integers are decoded with their
+/// sign.
+///
+/// ```rust
+/// use agsol_borsh_schema::BorshSchema;
+/// use solana_program::pubkey::Pubkey;
+///
+/// #[repr(C)]
+/// #[derive(BorshSchema, Clone, Copy)]
+/// #[schema(zero_copy)]
+/// struct PriceFeed {
+///     authority: Pubkey,
+///     prices: [u64; 16],
+///     head: u32,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use agsol_borsh_schema::BorshSchema;
+///
+/// #[repr(C)]
+/// #[derive(BorshSchema, Clone, Copy)]
+/// #[schema(zero_copy)]
+/// struct Flag {
+///     set: bool,
+/// }
+/// ```
+///
+/// # Discriminators
+/// Accounts prefixed with an Anchor discriminator (the first 8 bytes of
+/// `sha256("account:<Name>")`) can be annotated with
+/// `#[schema(discriminator)]`, while a custom prefix can be provided as a hex
+/// string or a byte string via `#[schema(discriminator = "0xdeadbeef")]`. The
+/// derive generates a `DISCRIMINATOR` constant and the parser generates a
+/// `decode<Name>Account` TypeScript helper that verifies and strips the
+/// prefix before decoding the data, throwing an error on a mismatch.
+///
+/// ```rust
+/// use agsol_borsh_schema::BorshSchema;
+/// use solana_program::pubkey::Pubkey;
+///
+/// #[derive(BorshSchema)]
+/// #[schema(discriminator)]
+/// struct Vault {
+///     owner: Pubkey,
+///     amount: u64,
+/// }
+///
+/// assert_eq!(Vault::DISCRIMINATOR.len(), 8);
+/// ```
 pub trait BorshSchema {}
+
+/// Implemented by the `BorshSchema` derive for data structures annotated with
+/// `#[schema(zero_copy)]`, so that they may be fields of other zero-copy
+/// layouts.
+pub trait ZeroCopySchema {}
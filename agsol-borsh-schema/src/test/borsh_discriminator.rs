@@ -0,0 +1,67 @@
+use super::TEST_DATA_DIRECTORY;
+use crate::*;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+use solana_program::hash::hash;
+use solana_program::pubkey::Pubkey;
+
+use std::fs;
+use std::io::Write;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestData {
+    vault: Vec<u8>,
+    tagged_vault: Vec<u8>,
+}
+
+#[derive(BorshSchema, BorshSerialize, BorshDeserialize, Clone, Debug)]
+#[schema(discriminator)]
+pub struct Vault {
+    owner: Pubkey,
+    amount: u64,
+}
+
+#[derive(BorshSchema, BorshSerialize, BorshDeserialize, Clone, Debug)]
+#[schema(discriminator = "0xdeadbeef")]
+pub struct TaggedVault {
+    amount: u64,
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_discriminator.rs").unwrap();
+    assert_eq!(layouts.len(), 2);
+
+    let anchor = &hash(b"account:Vault").to_bytes()[..8];
+    assert_eq!(Vault::DISCRIMINATOR, anchor);
+    assert_eq!(anchor_discriminator("Vault"), anchor);
+    assert_eq!(layouts[0].discriminator.as_deref(), Some(anchor));
+    assert_eq!(TaggedVault::DISCRIMINATOR, &[0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(
+        layouts[1].discriminator.as_deref(),
+        Some(TaggedVault::DISCRIMINATOR)
+    );
+
+    let vault = Vault {
+        owner: Pubkey::new(&[5; 32]),
+        amount: 1_000_000,
+    };
+    let tagged_vault = TaggedVault { amount: 42 };
+
+    let mut vault_data = Vault::DISCRIMINATOR.to_vec();
+    vault_data.append(&mut vault.try_to_vec().unwrap());
+    let mut tagged_vault_data = TaggedVault::DISCRIMINATOR.to_vec();
+    tagged_vault_data.append(&mut tagged_vault.try_to_vec().unwrap());
+
+    let test_data = TestData {
+        vault: vault_data,
+        tagged_vault: tagged_vault_data,
+    };
+
+    fs::create_dir_all(TEST_DATA_DIRECTORY).unwrap();
+    let mut file =
+        fs::File::create(String::from(TEST_DATA_DIRECTORY) + "/test_discriminator.json").unwrap();
+    write!(file, "{}", serde_json::to_string(&test_data).unwrap()).unwrap();
+}
@@ -0,0 +1,77 @@
+use super::TEST_DATA_DIRECTORY;
+use crate::*;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+
+use std::fs;
+use std::io::Write;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestData {
+    program_id: Vec<u8>,
+    authority: Vec<u8>,
+    pool_index: u64,
+    epoch: u16,
+    address: Vec<u8>,
+    bump: u8,
+}
+
+#[derive(BorshSchema, BorshSerialize, BorshDeserialize, Clone, Debug)]
+#[seeds(b"pool", authority: Pubkey, pool_index: u64, epoch: u16(be))]
+pub struct SeededPool {
+    authority: Pubkey,
+    pool_index: u64,
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/borsh_seeds.rs").unwrap();
+    assert_eq!(layouts.len(), 1);
+    assert_eq!(layouts[0].name, "SeededPool");
+    assert_eq!(layouts[0].seeds.len(), 4);
+
+    let program_id = Pubkey::new(&[7; 32]);
+    let authority = Pubkey::new(&[42; 32]);
+    let pool_index = 1234567890123;
+    let epoch = 300;
+
+    assert_eq!(SeededPool::SEED_PREFIX, b"pool");
+    assert_eq!(
+        SeededPool::seeds(&authority, pool_index, epoch),
+        vec![
+            b"pool".to_vec(),
+            authority.to_bytes().to_vec(),
+            pool_index.to_le_bytes().to_vec(),
+            epoch.to_be_bytes().to_vec(),
+        ]
+    );
+
+    let (address, bump) = SeededPool::find_address(&program_id, &authority, pool_index, epoch);
+    let expected = Pubkey::find_program_address(
+        &[
+            b"pool",
+            authority.as_ref(),
+            &pool_index.to_le_bytes(),
+            &epoch.to_be_bytes(),
+        ],
+        &program_id,
+    );
+    assert_eq!((address, bump), expected);
+
+    let test_data = TestData {
+        program_id: program_id.to_bytes().to_vec(),
+        authority: authority.to_bytes().to_vec(),
+        pool_index,
+        epoch,
+        address: address.to_bytes().to_vec(),
+        bump,
+    };
+
+    fs::create_dir_all(TEST_DATA_DIRECTORY).unwrap();
+    let mut file =
+        fs::File::create(String::from(TEST_DATA_DIRECTORY) + "/test_seeds.json").unwrap();
+    write!(file, "{}", serde_json::to_string(&test_data).unwrap()).unwrap();
+}
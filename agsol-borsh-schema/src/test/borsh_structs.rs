@@ -42,13 +42,24 @@ pub struct OtherState {
 #[derive(BorshSchema, BorshSerialize, BorshDeserialize, Clone, Debug)]
 pub struct TupleStruct(u8, pub i32, pub OtherState);
 
+#[allow(dead_code)]
+#[derive(BorshSchema, BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct SignedState {
+    delta: i64,
+    tick: i8,
+    spread: i16,
+    offset: i32,
+    total: i128,
+}
+
 #[test]
 fn generate_layout_from_this_file() {
     let layouts = generate_layout_from_file("src/test/borsh_structs.rs").unwrap();
-    assert_eq!(layouts.len(), 3);
+    assert_eq!(layouts.len(), 4);
     assert_eq!(layouts[0].name, "TestStruct");
     assert_eq!(layouts[1].name, "OtherState");
     assert_eq!(layouts[2].name, "TupleStruct");
+    assert_eq!(layouts[3].name, "SignedState");
 
     let test_struct_none = TestStruct {
         field_a: 45678910,
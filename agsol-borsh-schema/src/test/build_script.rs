@@ -0,0 +1,109 @@
+use crate::*;
+
+use std::fs;
+use std::path::PathBuf;
+
+fn test_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("agsol-borsh-schema-{}", name));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(directory.join("src/nested")).unwrap();
+    directory
+}
+
+#[test]
+fn build_schema_writes_only_changed_output() {
+    let directory = test_directory("build-ok");
+    fs::write(
+        directory.join("src/lib.rs"),
+        "#[derive(BorshSchema)]\nstruct Foo {\n    a: u64,\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        directory.join("src/nested/bar.rs"),
+        "#[derive(BorshSchema)]\nenum Bar {\n    A,\n    B(u8),\n}\n",
+    )
+    .unwrap();
+    let output = directory.join("output");
+
+    build_schema_from(directory.join("src"), &output).unwrap();
+    let schema = fs::read_to_string(output.join("schema.ts")).unwrap();
+    assert!(schema.contains("export class Foo extends Struct"));
+    assert!(schema.contains("export class Bar extends Enum"));
+
+    // an unchanged schema is not rewritten
+    assert!(!write_if_changed(output.join("schema.ts"), &schema).unwrap());
+    let modified = fs::metadata(output.join("schema.ts"))
+        .unwrap()
+        .modified()
+        .unwrap();
+    build_schema_from(directory.join("src"), &output).unwrap();
+    assert_eq!(
+        fs::metadata(output.join("schema.ts"))
+            .unwrap()
+            .modified()
+            .unwrap(),
+        modified
+    );
+    assert!(write_if_changed(output.join("schema.ts"), "").unwrap());
+}
+
+#[test]
+fn build_schema_reports_error_locations() {
+    let directory = test_directory("build-err");
+    fs::write(
+        directory.join("src/lib.rs"),
+        "#[derive(BorshSchema)]\nstruct Foo {\n    a: u64,\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        directory.join("src/nested/bad.rs"),
+        "\n#[derive(BorshSchema)]\n#[seeds(b\"x\", name: String)]\nstruct Bad {\n    a: u64,\n}\n\n#[derive(BorshSchema)]\n#[schema(unknown)]\nstruct Worse;\n",
+    )
+    .unwrap();
+    let output = directory.join("output");
+
+    let error = build_schema_from(directory.join("src"), &output)
+        .unwrap_err()
+        .to_string();
+    // the first error of a file is reported with the span taken from `syn`
+    assert!(
+        error.contains("bad.rs:3:21: seed parameters must be `Pubkey` or integer types"),
+        "{}",
+        error
+    );
+    assert!(!output.join("schema.ts").exists());
+
+    let error = generate_layout_from_file(directory.join("src/nested/bad.rs"))
+        .unwrap_err()
+        .downcast::<LayoutError>()
+        .unwrap();
+    assert_eq!((error.line, error.column), (3, 21));
+
+    fs::write(
+        directory.join("src/nested/bad.rs"),
+        "#[derive(BorshSchema)]\n#[schema(unknown)]\nstruct Worse;\n",
+    )
+    .unwrap();
+    let error = generate_layout_from_file(directory.join("src/nested/bad.rs"))
+        .unwrap_err()
+        .downcast::<LayoutError>()
+        .unwrap();
+    assert_eq!((error.line, error.column), (2, 10));
+    assert_eq!(error.message, "unknown schema option `unknown`");
+
+    // errors without a span point at the name of the data structure
+    fs::write(
+        directory.join("src/nested/bad.rs"),
+        "#[derive(BorshSchema)]\n#[schema(zero_copy)]\nstruct Worse {\n    a: u64,\n}\n",
+    )
+    .unwrap();
+    let error = generate_layout_from_file(directory.join("src/nested/bad.rs"))
+        .unwrap_err()
+        .downcast::<LayoutError>()
+        .unwrap();
+    assert_eq!((error.line, error.column), (3, 8));
+    assert_eq!(
+        error.message,
+        "zero-copy layout `Worse` must be `#[repr(C)]` and not packed"
+    );
+}
@@ -0,0 +1,101 @@
+use super::borsh_btree::BTreeWrapper;
+use super::borsh_discriminator::{TaggedVault, Vault};
+use super::borsh_enums::{RandomStruct, TestEnum};
+use super::borsh_seeds::SeededPool;
+use super::borsh_structs::{OtherState, SignedState, TestStruct, TupleStruct};
+use crate::*;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+fn roundtrip<T: BorshSerialize + BorshDeserialize>(bytes: &[u8]) -> Vec<u8> {
+    T::try_from_slice(bytes).unwrap().try_to_vec().unwrap()
+}
+
+#[test]
+fn golden_vectors_roundtrip_in_rust() {
+    let layouts = generate_layouts("src/test").unwrap();
+    let vectors = golden_vectors(&layouts).unwrap();
+
+    // every variant of the enum has its own fixture, variant layouts have none
+    assert_eq!(
+        vectors
+            .iter()
+            .filter(|vector| vector.layout == "TestEnum")
+            .count(),
+        7
+    );
+    assert!(vectors
+        .iter()
+        .all(|vector| !vector.layout.starts_with("TestEnumVariant")));
+
+    for vector in &vectors {
+        let roundtrip_bytes = match vector.layout.as_str() {
+            "BTreeWrapper" => roundtrip::<BTreeWrapper>(&vector.bytes),
+            "RandomStruct" => roundtrip::<RandomStruct>(&vector.bytes),
+            "TestEnum" => roundtrip::<TestEnum>(&vector.bytes),
+            "SeededPool" => roundtrip::<SeededPool>(&vector.bytes),
+            "OtherState" => roundtrip::<OtherState>(&vector.bytes),
+            "TestStruct" => roundtrip::<TestStruct>(&vector.bytes),
+            "TupleStruct" => roundtrip::<TupleStruct>(&vector.bytes),
+            "SignedState" => roundtrip::<SignedState>(&vector.bytes),
+            "TaggedVault" => roundtrip::<TaggedVault>(&vector.bytes),
+            "Vault" => roundtrip::<Vault>(&vector.bytes),
+            // zero-copy layouts are checked below
+            "ZeroCopyAccount" | "ZeroCopyEntry" | "ZeroCopyTick" => {
+                assert!(vector.zero_copy);
+                continue;
+            }
+            name => panic!("unexpected layout {}", name),
+        };
+        assert_eq!(roundtrip_bytes, vector.bytes, "{}", vector.file_stem());
+    }
+
+    let find = |stem: &str| {
+        vectors
+            .iter()
+            .find(|vector| vector.file_stem() == stem)
+            .unwrap()
+    };
+
+    // integers are maxed out (minimized) and perturbed by the field index
+    let other_state_max = find("OtherState_max");
+    let mut expected = vec![0xff; 16];
+    expected[8] = 0xfe;
+    expected[15] = 0x7f;
+    assert_eq!(other_state_max.bytes, expected);
+    let other_state_min = find("OtherState_min");
+    let mut expected = vec![0; 16];
+    expected[8] = 1;
+    expected[15] = 0x80;
+    assert_eq!(other_state_min.bytes, expected);
+    assert_eq!(
+        other_state_min.value["timestamp"],
+        serde_json::json!({ "$signed": (i64::MIN + 1).to_string(), "bits": 64 })
+    );
+    // the first signed field is the minimum of its type
+    let signed_state_min = find("SignedState_min");
+    assert_eq!(signed_state_min.bytes[..8], i64::MIN.to_le_bytes());
+    assert_eq!(signed_state_min.bytes[8], (i8::MIN + 1) as u8);
+    let zero_copy_tick_min = find("ZeroCopyTick_min");
+    assert_eq!(zero_copy_tick_min.bytes.len(), 32);
+    assert_eq!(zero_copy_tick_min.bytes[..8], i64::MIN.to_le_bytes());
+    assert_eq!(zero_copy_tick_min.bytes[9], 0);
+
+    // zero-copy fixtures contain the C memory layout with zeroed padding
+    let mut expected = vec![0xff; 8];
+    expected.extend_from_slice(&[0xfe, 0xff, 0xff, 0xff, 0xfd, 0, 0, 0]);
+    assert_eq!(find("ZeroCopyEntry_max").bytes, expected);
+    for case in ["min", "max"] {
+        let vector = find(&format!("ZeroCopyAccount_{}", case));
+        assert_eq!(vector.bytes.len(), 112);
+        // padding after `version` and `counter`
+        assert_eq!(vector.bytes[33], 0);
+        assert_eq!(vector.bytes[36..40], [0; 4]);
+    }
+}
+
+#[test]
+fn generate_golden_vectors_from_test_directory() {
+    let layouts = generate_layouts("src/test").unwrap();
+    generate_golden_vectors(&layouts, "test-output").unwrap();
+}
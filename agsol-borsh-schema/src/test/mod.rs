@@ -1,6 +1,11 @@
 mod borsh_btree;
+mod borsh_discriminator;
 mod borsh_enums;
+mod borsh_seeds;
 mod borsh_structs;
+mod build_script;
 mod generate_output;
+mod golden_vectors;
+mod zero_copy;
 
 const TEST_DATA_DIRECTORY: &str = "test-data";
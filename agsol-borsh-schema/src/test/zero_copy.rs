@@ -0,0 +1,86 @@
+use crate::*;
+
+use solana_program::pubkey::Pubkey;
+
+use std::mem::size_of;
+use std::ptr::addr_of;
+
+#[repr(C)]
+#[derive(BorshSchema, Clone, Copy)]
+#[schema(zero_copy)]
+pub struct ZeroCopyEntry {
+    price: u64,
+    slot: u32,
+    flags: u8,
+}
+
+#[repr(C)]
+#[derive(BorshSchema, Clone, Copy)]
+#[schema(zero_copy)]
+pub struct ZeroCopyAccount {
+    version: u8,
+    authority: Pubkey,
+    counter: u16,
+    history: [ZeroCopyEntry; 3],
+    total: u128,
+    seed: [u8; 3],
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(BorshSchema, Clone, Copy)]
+#[schema(zero_copy)]
+pub struct ZeroCopyTick {
+    delta: i64,
+    tick: i8,
+    spread: i16,
+    offset: i32,
+    total: i128,
+}
+
+#[test]
+fn generate_layout_from_this_file() {
+    let layouts = generate_layout_from_file("src/test/zero_copy.rs").unwrap();
+    assert_eq!(layouts.len(), 3);
+    assert!(layouts.iter().all(|layout| layout.zero_copy));
+
+    let account = ZeroCopyAccount {
+        version: 1,
+        authority: Pubkey::new_unique(),
+        counter: 2,
+        history: [ZeroCopyEntry {
+            price: 3,
+            slot: 4,
+            flags: 5,
+        }; 3],
+        total: 6,
+        seed: [7; 3],
+    };
+    let base = addr_of!(account) as usize;
+    let host_offsets = vec![
+        addr_of!(account.version) as usize - base,
+        addr_of!(account.authority) as usize - base,
+        addr_of!(account.counter) as usize - base,
+        addr_of!(account.history) as usize - base,
+    ];
+
+    // `u128` is 8-aligned on-chain, so only the fields before it are laid out
+    // the same way on every host
+    let account_layout = c_layout(&layouts[1], &layouts).unwrap();
+    let offsets = account_layout
+        .fields
+        .iter()
+        .map(|field| field.offset)
+        .collect::<Vec<_>>();
+    assert_eq!(offsets[..4], host_offsets);
+    assert_eq!(offsets[4..], [88, 104]);
+    assert_eq!(account_layout.size, 112);
+    assert_eq!(account_layout.align, 8);
+
+    let entry_layout = c_layout(&layouts[0], &layouts).unwrap();
+    assert_eq!(entry_layout.size, size_of::<ZeroCopyEntry>());
+    assert_eq!(entry_layout.trailing_padding, 3);
+
+    let tick_layout = c_layout(&layouts[2], &layouts).unwrap();
+    assert_eq!(tick_layout.size, size_of::<ZeroCopyTick>());
+}
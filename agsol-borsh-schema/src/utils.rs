@@ -13,11 +13,12 @@
 //!
 //! ```
 
-use crate::layout::{Kind, Layout, LayoutField};
+use crate::build::LayoutError;
+use crate::layout::{Kind, Layout, LayoutField, Seed};
+use agsol_borsh_schema_parse::{is_repr_c, SchemaOptions};
 
 use std::ffi::OsStr;
 use std::fs;
-use std::io::Write;
 use std::path::Path;
 
 /// Traverses all files in the input directory and looks for data structures
@@ -43,54 +44,96 @@ pub fn generate_layout_from_file(filepath: impl AsRef<Path>) -> Result<Vec<Layou
     if filepath.as_ref().extension() != Some(OsStr::new("rs")) {
         return Ok(Vec::new());
     }
+    let filepath = filepath.as_ref();
     let code = fs::read_to_string(filepath)?;
-    let syntax = syn::parse_file(&code)?;
+    let syntax =
+        syn::parse_file(&code).map_err(|e| LayoutError::new(filepath, e.span(), e.to_string()))?;
     let mut layouts = Vec::<Layout>::new();
     for item in syntax.items {
-        match item {
-            syn::Item::Struct(ref item_struct) => {
-                for attr in &item_struct.attrs {
-                    let attribute_string = attr.tokens.to_string();
-                    if attribute_string.contains("BorshSchema") {
-                        layouts.push(Layout::from_tokens(
-                            &item_struct.ident.to_string(),
-                            &mut item_struct.fields.iter(),
-                        )?);
+        let mut item_layouts = generate_layouts_from_item(&item)
+            .map_err(|e| LayoutError::from_item(filepath, &item, e))?;
+        layouts.append(&mut item_layouts);
+    }
+    Ok(layouts)
+}
+
+/// Generates the layouts of a single `struct` or `enum` item annotated with
+/// `#[derive(BorshSchema)]`.
+fn generate_layouts_from_item(item: &syn::Item) -> Result<Vec<Layout>, anyhow::Error> {
+    let mut layouts = Vec::<Layout>::new();
+    match item {
+        syn::Item::Struct(ref item_struct) => {
+            for attr in &item_struct.attrs {
+                let attribute_string = attr.tokens.to_string();
+                if attribute_string.contains("BorshSchema") {
+                    let mut layout = Layout::from_tokens(
+                        &item_struct.ident.to_string(),
+                        &mut item_struct.fields.iter(),
+                    )?;
+                    layout.seeds = find_seeds(&item_struct.attrs)?;
+                    let options = SchemaOptions::from_attributes(
+                        &item_struct.ident.to_string(),
+                        &item_struct.attrs,
+                    )?;
+                    if options.zero_copy && !is_repr_c(&item_struct.attrs)? {
+                        anyhow::bail!(
+                            "zero-copy layout `{}` must be `#[repr(C)]` and not packed",
+                            item_struct.ident
+                        );
                     }
+                    layout.zero_copy = options.zero_copy;
+                    layout.discriminator = options.discriminator;
+                    layouts.push(layout);
                 }
             }
-            syn::Item::Enum(ref item_enum) => {
-                for attr in &item_enum.attrs {
-                    let attribute_string = attr.tokens.to_string();
-                    if attribute_string.contains("BorshSchema") {
-                        let mut enum_layout = Layout {
-                            name: item_enum.ident.to_string(),
-                            kind: Kind::Enum,
-                            fields: Vec::new(),
-                        };
-                        let parent_name = item_enum.ident.to_string();
-                        let mut variant_layouts = item_enum
-                            .variants
-                            .iter()
-                            .map(|variant| {
-                                let name = parent_name.clone() + &variant.ident.to_string();
-                                enum_layout
-                                    .fields
-                                    .push(LayoutField::from_enum_variant(&name)?);
-                                Layout::from_tokens(&name, &mut variant.fields.iter())
-                            })
-                            .collect::<Result<Vec<_>, _>>()?;
-                        layouts.push(enum_layout);
-                        layouts.append(&mut variant_layouts);
-                    }
+        }
+        syn::Item::Enum(ref item_enum) => {
+            for attr in &item_enum.attrs {
+                let attribute_string = attr.tokens.to_string();
+                if attribute_string.contains("BorshSchema") {
+                    let options = SchemaOptions::from_attributes(
+                        &item_enum.ident.to_string(),
+                        &item_enum.attrs,
+                    )?;
+                    let mut enum_layout = Layout {
+                        name: item_enum.ident.to_string(),
+                        kind: Kind::Enum,
+                        fields: Vec::new(),
+                        seeds: find_seeds(&item_enum.attrs)?,
+                        zero_copy: options.zero_copy,
+                        discriminator: options.discriminator,
+                    };
+                    let parent_name = item_enum.ident.to_string();
+                    let mut variant_layouts = item_enum
+                        .variants
+                        .iter()
+                        .map(|variant| {
+                            let name = parent_name.clone() + &variant.ident.to_string();
+                            enum_layout
+                                .fields
+                                .push(LayoutField::from_enum_variant(&name)?);
+                            Layout::from_tokens(&name, &mut variant.fields.iter())
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    layouts.push(enum_layout);
+                    layouts.append(&mut variant_layouts);
                 }
             }
-            _ => {}
         }
+        _ => {}
     }
     Ok(layouts)
 }
 
+/// Parses the PDA seed recipe of a data structure if it has a `seeds`
+/// attribute.
+fn find_seeds(attrs: &[syn::Attribute]) -> Result<Vec<Seed>, anyhow::Error> {
+    attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("seeds"))
+        .map_or_else(|| Ok(Vec::new()), Seed::from_attribute)
+}
+
 /// Writes the generated layouts into a file in the provided output directory.
 pub fn generate_output(
     layouts: &[Layout],
@@ -106,13 +149,31 @@ pub fn generate_output(
         .map(|layout| layout.to_ts_class())
         .collect::<String>();
 
+    let pda_helpers_string = layouts
+        .iter()
+        .filter_map(|layout| layout.to_ts_pda_helper())
+        .collect::<String>();
+
+    let zero_copy_decoders_string = layouts
+        .iter()
+        .map(|layout| layout.to_ts_zero_copy_decoder(layouts))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect::<String>();
+
+    let discriminator_helpers_string = layouts
+        .iter()
+        .filter_map(|layout| layout.to_ts_discriminator_helper())
+        .collect::<String>();
+
     let schema = format!(
         r#"export const SCHEMA = new Map<any, any>([{}
 ]);"#,
         schema_string
     );
 
-    let imports = String::from(
+    let mut imports = String::from(
         r#"import { PublicKey } from "@solana/web3.js";
 import BN from "bn.js";
 import Enum from "./extensions/enum";
@@ -123,9 +184,33 @@ borshPublicKey();
 
 "#,
     );
+    if layouts
+        .iter()
+        .any(|layout| layout.discriminator.is_some() && !layout.zero_copy)
+    {
+        imports.insert_str(0, "import { deserializeUnchecked } from \"borsh\";\n");
+    }
 
     fs::create_dir_all(&output_directory)?;
-    let mut file = fs::File::create(output_directory.as_ref().join("schema.ts"))?;
-    write!(file, "{}", imports + &classes_string + &schema)?;
+    write_if_changed(
+        output_directory.as_ref().join("schema.ts"),
+        &(imports
+            + &classes_string
+            + &pda_helpers_string
+            + &zero_copy_decoders_string
+            + &discriminator_helpers_string
+            + &schema),
+    )?;
     Ok(())
 }
+
+/// Writes the contents into the file unless it already holds exactly the
+/// same contents, so that unchanged outputs don't trigger rebuilds of
+/// downstream tooling. Returns whether the file was written.
+pub fn write_if_changed(path: impl AsRef<Path>, contents: &str) -> Result<bool, anyhow::Error> {
+    if matches!(fs::read_to_string(&path), Ok(current) if current == contents) {
+        return Ok(false);
+    }
+    fs::write(path, contents)?;
+    Ok(true)
+}
@@ -0,0 +1,106 @@
+import {
+    BTreeWrapper,
+    OtherState,
+    RandomStruct,
+    SeededPool,
+    SignedState,
+    TaggedVault,
+    TestEnum,
+    TestStruct,
+    TupleStruct,
+    Vault,
+    ZeroCopyAccount,
+    ZeroCopyEntry,
+    ZeroCopyTick,
+    decodeZeroCopyAccount,
+    decodeZeroCopyEntry,
+    decodeZeroCopyTick,
+    SCHEMA
+} from "./schema";
+import { PublicKey } from "@solana/web3.js";
+import { serialize, deserializeUnchecked } from "borsh";
+import BN from "bn.js";
+
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+
+const FIXTURES: [any, string, ((data: Buffer) => any) | null][] = [
+    [ZeroCopyEntry, "ZeroCopyEntry_min", decodeZeroCopyEntry],
+    [ZeroCopyEntry, "ZeroCopyEntry_max", decodeZeroCopyEntry],
+    [ZeroCopyAccount, "ZeroCopyAccount_min", decodeZeroCopyAccount],
+    [ZeroCopyAccount, "ZeroCopyAccount_max", decodeZeroCopyAccount],
+    [ZeroCopyTick, "ZeroCopyTick_min", decodeZeroCopyTick],
+    [ZeroCopyTick, "ZeroCopyTick_max", decodeZeroCopyTick],
+    [SeededPool, "SeededPool_min", null],
+    [SeededPool, "SeededPool_max", null],
+    [Vault, "Vault_min", null],
+    [Vault, "Vault_max", null],
+    [TaggedVault, "TaggedVault_min", null],
+    [TaggedVault, "TaggedVault_max", null],
+    [BTreeWrapper, "BTreeWrapper_min", null],
+    [BTreeWrapper, "BTreeWrapper_max", null],
+    [TestStruct, "TestStruct_min", null],
+    [TestStruct, "TestStruct_max", null],
+    [OtherState, "OtherState_min", null],
+    [OtherState, "OtherState_max", null],
+    [TupleStruct, "TupleStruct_min", null],
+    [TupleStruct, "TupleStruct_max", null],
+    [SignedState, "SignedState_min", null],
+    [SignedState, "SignedState_max", null],
+    [RandomStruct, "RandomStruct_min", null],
+    [RandomStruct, "RandomStruct_max", null],
+    [TestEnum, "TestEnum_testEnumVariantA", null],
+    [TestEnum, "TestEnum_testEnumVariantB", null],
+    [TestEnum, "TestEnum_testEnumVariantC", null],
+    [TestEnum, "TestEnum_testEnumVariantD", null],
+    [TestEnum, "TestEnum_testEnumVariantE", null],
+    [TestEnum, "TestEnum_testEnumVariantF", null],
+    [TestEnum, "TestEnum_testEnumVariantG", null],
+];
+
+function assertGolden(actual: any, expected: any, at: string) {
+    if (expected === null) {
+        assert(actual === null || actual === undefined, at);
+    } else if (typeof expected === "boolean") {
+        assert(Number(actual) === Number(expected), at);
+    } else if (typeof expected === "string") {
+        assert(actual.toString() === expected, at);
+    } else if (Array.isArray(expected)) {
+        const items = actual instanceof PublicKey ? Array.from(actual.toBytes()) : Array.from(actual);
+        assert(items.length === expected.length, at);
+        expected.forEach((item, i) => assertGolden(items[i], item, `${at}[${i}]`));
+    } else if ("$signed" in expected) {
+        // borsh-js decodes signed integers as their unsigned two's complement
+        const value = new BN(actual.toString());
+        const signed = value.isNeg() ? value : value.fromTwos(expected.bits);
+        assert(signed.toString() === expected.$signed, at);
+    } else if ("$map" in expected) {
+        const entries = Array.from(actual.entries());
+        assert(entries.length === expected.$map.length, at);
+        expected.$map.forEach(([key, value], i) => {
+            assertGolden(entries[i][0], key, `${at}.key[${i}]`);
+            assertGolden(entries[i][1], value, `${at}.value[${i}]`);
+        });
+    } else {
+        Object.keys(expected).forEach((key) => assertGolden(actual[key], expected[key], `${at}.${key}`));
+    }
+}
+
+for (const [layout, fixture, decode] of FIXTURES) {
+    const fixturePath = path.join(__dirname, "golden", fixture);
+    const expected = JSON.parse(fs.readFileSync(fixturePath + ".json").toString());
+    const bytes = Buffer.from(fs.readFileSync(fixturePath + ".hex").toString().trim(), "hex");
+
+    if (decode !== null) {
+        // zero-copy fixtures contain the C memory layout
+        assertGolden(decode(bytes), expected, fixture);
+        continue;
+    }
+
+    const decoded = deserializeUnchecked(SCHEMA, layout, bytes);
+    assertGolden(decoded, expected, fixture);
+
+    const encoded = Buffer.from(serialize(SCHEMA, decoded));
+    assert(encoded.equals(bytes), fixture);
+}
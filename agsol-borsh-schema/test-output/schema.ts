@@ -1,3 +1,4 @@
+import { deserializeUnchecked } from "borsh";
 import { PublicKey } from "@solana/web3.js";
 import BN from "bn.js";
 import Enum from "./extensions/enum";
@@ -6,6 +7,43 @@ import { borshPublicKey } from "./extensions/publicKey";
 
 borshPublicKey();
 
+export class ZeroCopyEntry extends Struct {
+    price: BN;
+    slot: number;
+    flags: number;
+};
+
+export class ZeroCopyAccount extends Struct {
+    version: number;
+    authority: PublicKey;
+    counter: number;
+    history: ZeroCopyEntry[];
+    total: BN;
+    seed: [3];
+};
+
+export class ZeroCopyTick extends Struct {
+    delta: BN;
+    tick: number;
+    spread: number;
+    offset: number;
+    total: BN;
+};
+
+export class SeededPool extends Struct {
+    authority: PublicKey;
+    poolIndex: BN;
+};
+
+export class Vault extends Struct {
+    owner: PublicKey;
+    amount: BN;
+};
+
+export class TaggedVault extends Struct {
+    amount: BN;
+};
+
 export class BTreeWrapper extends Struct {
     map0: Map<[32], PublicKey>;
     map1: Map<string, number | null>;
@@ -29,6 +67,14 @@ export class TupleStruct extends Struct {
     unnamed_2: OtherState;
 };
 
+export class SignedState extends Struct {
+    delta: BN;
+    tick: number;
+    spread: number;
+    offset: number;
+    total: BN;
+};
+
 export class RandomStruct extends Struct {
     fieldA: string;
     fieldB: [2] | null;
@@ -73,7 +119,167 @@ export class TestEnumVariantG extends Struct {
     zello: boolean;
 };
 
+export async function findSeededPoolAddress(
+    programId: PublicKey,
+    authority: PublicKey,
+    poolIndex: BN,
+    epoch: number,
+): Promise<[PublicKey, number]> {
+    return await PublicKey.findProgramAddress(
+        [
+            Buffer.from("pool"),
+            authority.toBuffer(),
+            new BN(poolIndex).toTwos(64).toArrayLike(Buffer, "le", 8),
+            new BN(epoch).toTwos(16).toArrayLike(Buffer, "be", 2),
+        ],
+        programId
+    );
+}
+
+export const ZERO_COPY_ENTRY_LAYOUT = {
+    span: 16,
+    align: 8,
+    offsets: {
+        price: 0,
+        slot: 8,
+        flags: 12,
+    },
+};
+
+export function decodeZeroCopyEntry(data: Buffer, offset: number = 0): ZeroCopyEntry {
+    return new ZeroCopyEntry({
+        price: new BN(data.subarray(offset + 0, offset + 0 + 8), "le"),
+        slot: data.readUInt32LE(offset + 8),
+        flags: data.readUInt8(offset + 12),
+    });
+}
+
+export const ZERO_COPY_ACCOUNT_LAYOUT = {
+    span: 112,
+    align: 8,
+    offsets: {
+        version: 0,
+        authority: 1,
+        counter: 34,
+        history: 40,
+        total: 88,
+        seed: 104,
+    },
+};
+
+export function decodeZeroCopyAccount(data: Buffer, offset: number = 0): ZeroCopyAccount {
+    return new ZeroCopyAccount({
+        version: data.readUInt8(offset + 0),
+        authority: new PublicKey(data.subarray(offset + 1, offset + 1 + 32)),
+        counter: data.readUInt16LE(offset + 34),
+        history: Array.from({ length: 3 }, (_, i0) => decodeZeroCopyEntry(data, offset + 40 + i0 * 16)),
+        total: new BN(data.subarray(offset + 88, offset + 88 + 16), "le"),
+        seed: data.slice(offset + 104, offset + 104 + 3),
+    });
+}
+
+export const ZERO_COPY_TICK_LAYOUT = {
+    span: 32,
+    align: 8,
+    offsets: {
+        delta: 0,
+        tick: 8,
+        spread: 10,
+        offset: 12,
+        total: 16,
+    },
+};
+
+export function decodeZeroCopyTick(data: Buffer, offset: number = 0): ZeroCopyTick {
+    return new ZeroCopyTick({
+        delta: new BN(data.subarray(offset + 0, offset + 0 + 8), "le").fromTwos(64),
+        tick: data.readInt8(offset + 8),
+        spread: data.readInt16LE(offset + 10),
+        offset: data.readInt32LE(offset + 12),
+        total: new BN(data.subarray(offset + 16, offset + 16 + 16), "le").fromTwos(128),
+    });
+}
+
+export const VAULT_DISCRIMINATOR = Buffer.from([211, 8, 232, 43, 2, 152, 117, 119]);
+
+export function decodeVaultAccount(data: Buffer): Vault {
+    if (data.length < 8 || !data.subarray(0, 8).equals(VAULT_DISCRIMINATOR)) {
+        throw new Error("invalid Vault account discriminator");
+    }
+    return deserializeUnchecked(SCHEMA, Vault, data.subarray(8));
+}
+
+export const TAGGED_VAULT_DISCRIMINATOR = Buffer.from([222, 173, 190, 239]);
+
+export function decodeTaggedVaultAccount(data: Buffer): TaggedVault {
+    if (data.length < 4 || !data.subarray(0, 4).equals(TAGGED_VAULT_DISCRIMINATOR)) {
+        throw new Error("invalid TaggedVault account discriminator");
+    }
+    return deserializeUnchecked(SCHEMA, TaggedVault, data.subarray(4));
+}
+
 export const SCHEMA = new Map<any, any>([
+    [
+            ZeroCopyEntry,
+            {
+                kind: 'struct', fields: [
+			['price', 'u64'],
+			['slot', 'u32'],
+			['flags', 'u8'],
+                ],
+            },
+    ],
+    [
+            ZeroCopyAccount,
+            {
+                kind: 'struct', fields: [
+			['version', 'u8'],
+			['authority', 'publicKey'],
+			['counter', 'u16'],
+			['history', [ZeroCopyEntry, 3]],
+			['total', 'u128'],
+			['seed', [3]],
+                ],
+            },
+    ],
+    [
+            ZeroCopyTick,
+            {
+                kind: 'struct', fields: [
+			['delta', 'u64'],
+			['tick', 'u8'],
+			['spread', 'u16'],
+			['offset', 'u32'],
+			['total', 'u128'],
+                ],
+            },
+    ],
+    [
+            SeededPool,
+            {
+                kind: 'struct', fields: [
+			['authority', 'publicKey'],
+			['poolIndex', 'u64'],
+                ],
+            },
+    ],
+    [
+            Vault,
+            {
+                kind: 'struct', fields: [
+			['owner', 'publicKey'],
+			['amount', 'u64'],
+                ],
+            },
+    ],
+    [
+            TaggedVault,
+            {
+                kind: 'struct', fields: [
+			['amount', 'u64'],
+                ],
+            },
+    ],
     [
             BTreeWrapper,
             {
@@ -113,6 +319,18 @@ export const SCHEMA = new Map<any, any>([
                 ],
             },
     ],
+    [
+            SignedState,
+            {
+                kind: 'struct', fields: [
+			['delta', 'u64'],
+			['tick', 'u8'],
+			['spread', 'u16'],
+			['offset', 'u32'],
+			['total', 'u128'],
+                ],
+            },
+    ],
     [
             RandomStruct,
             {
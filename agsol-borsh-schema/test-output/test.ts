@@ -12,6 +12,9 @@ import {
     TestEnumVariantE,
     TestEnumVariantF,
     TestEnumVariantG,
+    findSeededPoolAddress,
+    decodeVaultAccount,
+    decodeTaggedVaultAccount,
     SCHEMA
 } from "./schema";
 import { PublicKey } from "@solana/web3.js";
@@ -27,6 +30,10 @@ var data = fs.readFileSync("../test-data/test_enums.json");
 const enumData = JSON.parse(data.toString());
 var data = fs.readFileSync("../test-data/test_btree.json");
 const btreeData = JSON.parse(data.toString());
+var data = fs.readFileSync("../test-data/test_seeds.json");
+const seedData = JSON.parse(data.toString());
+var data = fs.readFileSync("../test-data/test_discriminator.json");
+const discriminatorData = JSON.parse(data.toString());
 
 // STRUCT TESTS
 let otherStateOne = new OtherState({
@@ -332,3 +339,23 @@ assert(wrapperDeserialized.map1.get("yello") == null);
 assert(wrapperDeserialized.map1.get("zello") === 44);
 assert(wrapperDeserialized.map2.get(168) === "value");
 assert(wrapperDeserialized.map2.get(169) === "values");
+
+// SEED TESTS
+findSeededPoolAddress(
+    new PublicKey(seedData.programId),
+    new PublicKey(seedData.authority),
+    new BN(seedData.poolIndex),
+    seedData.epoch,
+).then(([address, bump]) => {
+    assert(address.toString() === new PublicKey(seedData.address).toString());
+    assert(bump === seedData.bump);
+});
+
+// DISCRIMINATOR TESTS
+const vault = decodeVaultAccount(Buffer.from(discriminatorData.vault));
+assert(vault.owner.toString() === new PublicKey(new Array(32).fill(5)).toString());
+assert(vault.amount.toNumber() === 1000000);
+const taggedVault = decodeTaggedVaultAccount(Buffer.from(discriminatorData.taggedVault));
+assert(taggedVault.amount.toNumber() === 42);
+assert.throws(() => decodeVaultAccount(Buffer.from(discriminatorData.taggedVault)));
+assert.throws(() => decodeTaggedVaultAccount(Buffer.from([0xde, 0xad])));
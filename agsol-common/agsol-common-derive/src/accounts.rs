@@ -0,0 +1,164 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Data, DeriveInput, Expr, ExprArray, Fields, Ident, Token};
+
+/// A constraint set via the `account` attribute of a field.
+enum Constraint {
+    Signer,
+    Mut,
+    Owner(Box<Expr>),
+    Seeds(Box<ExprArray>),
+    RentExempt,
+}
+
+impl Parse for Constraint {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![mut]) {
+            input.parse::<Token![mut]>()?;
+            return Ok(Self::Mut);
+        }
+        let ident = input.parse::<Ident>()?;
+        match ident.to_string().as_str() {
+            "signer" => Ok(Self::Signer),
+            "rent_exempt" => Ok(Self::RentExempt),
+            "owner" => {
+                input.parse::<Token![=]>()?;
+                Ok(Self::Owner(Box::new(input.parse()?)))
+            }
+            "seeds" => {
+                input.parse::<Token![=]>()?;
+                Ok(Self::Seeds(Box::new(input.parse()?)))
+            }
+            _ => Err(syn::Error::new(ident.span(), "unknown account constraint")),
+        }
+    }
+}
+
+pub fn accounts(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`Accounts` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`Accounts` can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut lifetimes = input.generics.lifetimes().map(|def| &def.lifetime);
+    let (a, info) = match (lifetimes.next(), lifetimes.next()) {
+        (Some(a), Some(info)) => (a, info),
+        (Some(a), None) => (a, a),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.generics,
+                "expected the lifetimes of the account infos, e.g. `<'a, 'info>`",
+            ))
+        }
+    };
+
+    let mut needs_rent = false;
+    let mut parsed_fields = Vec::with_capacity(fields.len());
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let account_name = ident.to_string();
+        let mut checks = Vec::new();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("account"))
+        {
+            let constraints =
+                attr.parse_args_with(Punctuated::<Constraint, Token![,]>::parse_terminated)?;
+            for constraint in constraints {
+                checks.push(match constraint {
+                    Constraint::Signer => quote! {
+                        if !#ident.is_signer {
+                            ::solana_program::msg!("account `{}` is not a signer", #account_name);
+                            return Err(::solana_program::program_error::ProgramError::MissingRequiredSignature);
+                        }
+                    },
+                    Constraint::Mut => quote! {
+                        if !#ident.is_writable {
+                            ::solana_program::msg!("account `{}` is not writable", #account_name);
+                            return Err(::agsol_common::AccountStateError::NotWritable.into());
+                        }
+                    },
+                    Constraint::Owner(owner) => quote! {
+                        if #ident.owner != #owner {
+                            ::solana_program::msg!("account `{}` has an invalid owner", #account_name);
+                            return Err(::solana_program::program_error::ProgramError::IllegalOwner);
+                        }
+                    },
+                    Constraint::Seeds(seeds) => {
+                        let seeds = seeds.elems.iter();
+                        quote! {
+                            if let Err(error) = ::agsol_common::SignerPda::new_checked(
+                                &[#(::core::convert::AsRef::<[u8]>::as_ref(#seeds)),*],
+                                program_id,
+                                #ident,
+                            ) {
+                                ::solana_program::msg!("account `{}` has invalid seeds", #account_name);
+                                return Err(error);
+                            }
+                        }
+                    }
+                    Constraint::RentExempt => {
+                        needs_rent = true;
+                        quote! {
+                            if !rent.is_exempt(#ident.lamports(), #ident.data_len()) {
+                                ::solana_program::msg!("account `{}` is not rent exempt", #account_name);
+                                return Err(::solana_program::program_error::ProgramError::AccountNotRentExempt);
+                            }
+                        }
+                    }
+                });
+            }
+        }
+        parsed_fields.push(quote! {
+            let #ident = match accounts_iter.next() {
+                Some(account) => account,
+                None => {
+                    ::solana_program::msg!("account `{}` is missing", #account_name);
+                    return Err(::solana_program::program_error::ProgramError::NotEnoughAccountKeys);
+                }
+            };
+            #(#checks)*
+        });
+    }
+
+    let rent = if needs_rent {
+        quote! {
+            let rent = <::solana_program::rent::Rent as ::solana_program::sysvar::Sysvar>::get()?;
+        }
+    } else {
+        quote! {}
+    };
+    let idents = fields.iter().map(|field| &field.ident);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::agsol_common::Accounts<#a, #info> for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn from_account_infos(
+                program_id: &::solana_program::pubkey::Pubkey,
+                accounts: &#a [::solana_program::account_info::AccountInfo<#info>],
+            ) -> Result<Self, ::solana_program::program_error::ProgramError> {
+                #rent
+                let mut accounts_iter = accounts.iter();
+                #(#parsed_fields)*
+                Ok(Self { #(#idents),* })
+            }
+        }
+    })
+}
@@ -0,0 +1,163 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Data, DeriveInput, Fields, Ident, Path, Token};
+
+/// An option set via the `instruction` attribute of an enum or its variants.
+enum InstructionOption {
+    Log,
+    Handler(Path),
+}
+
+impl Parse for InstructionOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident = input.parse::<Ident>()?;
+        match ident.to_string().as_str() {
+            "log" => Ok(Self::Log),
+            "handler" => {
+                input.parse::<Token![=]>()?;
+                Ok(Self::Handler(input.parse()?))
+            }
+            _ => Err(syn::Error::new(ident.span(), "unknown instruction option")),
+        }
+    }
+}
+
+#[derive(Default)]
+struct InstructionOptions {
+    log: bool,
+    handler: Option<Path>,
+}
+
+impl InstructionOptions {
+    fn from_attributes(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("instruction"))
+        {
+            let parsed =
+                attr.parse_args_with(Punctuated::<InstructionOption, Token![,]>::parse_terminated)?;
+            for option in parsed {
+                match option {
+                    InstructionOption::Log => options.log = true,
+                    InstructionOption::Handler(path) => options.handler = Some(path),
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+pub fn instruction(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "`Instruction` can only be derived for enums",
+            ))
+        }
+    };
+    let enum_options = InstructionOptions::from_attributes(&input.attrs)?;
+    if let Some(handler) = enum_options.handler {
+        return Err(syn::Error::new_spanned(
+            handler,
+            "handlers can only be set for variants",
+        ));
+    }
+
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let options = InstructionOptions::from_attributes(&variant.attrs)?;
+            let ident = &variant.ident;
+            let handler = options.handler.unwrap_or_else(|| {
+                format_ident!("process_{}", to_snake_case(&ident.to_string())).into()
+            });
+            let log = if enum_options.log || options.log {
+                let log_message = format!("Instruction: {}", ident);
+                quote! { ::solana_program::msg!(#log_message); }
+            } else {
+                quote! {}
+            };
+            let (pattern, args) = match variant.fields {
+                Fields::Named(ref fields) => {
+                    let idents = fields
+                        .named
+                        .iter()
+                        .map(|field| field.ident.clone().unwrap())
+                        .collect::<Vec<_>>();
+                    (quote! { { #(#idents),* } }, idents)
+                }
+                Fields::Unnamed(ref fields) => {
+                    let idents = (0..fields.unnamed.len())
+                        .map(|i| format_ident!("arg_{}", i))
+                        .collect::<Vec<_>>();
+                    (quote! { ( #(#idents),* ) }, idents)
+                }
+                Fields::Unit => (quote! {}, Vec::new()),
+            };
+            Ok(quote! {
+                Self::#ident #pattern => {
+                    #log
+                    #handler(program_id, accounts #(, #args)*)
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let variant_count = data.variants.len();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Decodes the instruction and dispatches it to its handler.
+            pub fn process(
+                program_id: &::solana_program::pubkey::Pubkey,
+                accounts: &[::solana_program::account_info::AccountInfo],
+                instruction_data: &[u8],
+            ) -> ::solana_program::entrypoint::ProgramResult {
+                match instruction_data.first() {
+                    Some(tag) if (*tag as usize) < #variant_count => {}
+                    _ => {
+                        ::solana_program::msg!("unknown instruction");
+                        return Err(::agsol_common::InstructionError::UnknownInstruction.into());
+                    }
+                }
+                let instruction =
+                    <Self as ::borsh::BorshDeserialize>::try_from_slice(instruction_data)
+                        .map_err(|_| {
+                            ::solana_program::msg!("invalid instruction data");
+                            ::agsol_common::InstructionError::InvalidInstructionData
+                        })?;
+                match instruction {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake_case = String::with_capacity(name.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            // a capital starts a new word unless it continues a run of
+            // capitals, the last of which starts a new word if followed by
+            // a lowercase letter, e.g. `NFTMint` -> `nft_mint`
+            let prev_upper = i > 0 && chars[i - 1].is_uppercase();
+            let next_lower = matches!(chars.get(i + 1), Some(c) if c.is_lowercase());
+            if i != 0 && (!prev_upper || next_lower) {
+                snake_case.push('_');
+            }
+            snake_case.extend(c.to_lowercase());
+        } else {
+            snake_case.push(*c);
+        }
+    }
+    snake_case
+}
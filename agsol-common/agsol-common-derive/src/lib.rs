@@ -1,41 +1,32 @@
 extern crate proc_macro;
 
-use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote, ToTokens};
-use syn::{Attribute, Data, DeriveInput, Fields, Type};
+mod accounts;
+mod instruction;
 
-use std::collections::VecDeque;
-use std::str::FromStr;
+use agsol_borsh_schema_parse::{anchor_discriminator, parse_discriminator};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DeriveInput, Fields, GenericArgument, Generics, Ident, Lit, LitByteStr, Meta,
+    NestedMeta, PathArguments, Token, Type, WherePredicate,
+};
 
-#[proc_macro_derive(MaxSerializedLen, attributes(len))]
+#[proc_macro_derive(MaxSerializedLen, attributes(len, max_len))]
 pub fn impl_max_serialized_len(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
-    let len = match input.data {
-        Data::Struct(ref data) => fields_len(&data.fields),
-        Data::Enum(ref data) => {
-            let mut max_lengths = data
-                .variants
-                .iter()
-                .map(|variant| {
-                    if let Some(len) = find_len_attr(&variant.attrs) {
-                        len
-                    } else {
-                        fields_len(&variant.fields)
-                    }
-                })
-                .collect::<VecDeque<TokenStream2>>();
-            let mut max_len = max_lengths.pop_front().unwrap();
-            for len in max_lengths.into_iter() {
-                max_len = recourse_max_len(max_len, len);
-            }
-            quote! { 1 + #max_len }
-        }
-        Data::Union(_) => unimplemented!(),
+    let (len, generics) = match max_serialized_len(&input).and_then(|len| {
+        let bound = quote! { MaxSerializedLen };
+        max_len_generics(&input, &bound).map(|generics| (len, generics))
+    }) {
+        Ok(result) => result,
+        Err(err) => return err.to_compile_error().into(),
     };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let token_stream2 = quote! {
         impl #impl_generics MaxSerializedLen for #name #ty_generics #where_clause {
             const MAX_SERIALIZED_LEN: usize = #len;
@@ -44,74 +35,519 @@ pub fn impl_max_serialized_len(input: TokenStream) -> TokenStream {
     token_stream2.into()
 }
 
-fn recourse_max_len(a: TokenStream2, b: TokenStream2) -> TokenStream2 {
-    quote! { [#a, #b][((#a) < (#b)) as usize] }
+fn max_serialized_len(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    match input.data {
+        Data::Struct(ref data) => fields_len(&data.fields),
+        Data::Enum(ref data) => {
+            let max_lengths = data
+                .variants
+                .iter()
+                .map(|variant| match find_len_attr(&variant.attrs)? {
+                    Some(len) => Ok(len),
+                    None => fields_len(&variant.fields),
+                })
+                .collect::<syn::Result<Vec<TokenStream2>>>()?;
+            // NOTE the generated code has to stay linear in the number of
+            // variants, otherwise large enums hang the compiler
+            Ok(quote! {{
+                const fn max_len(lengths: &[usize]) -> usize {
+                    let mut max = 0;
+                    let mut i = 0;
+                    while i < lengths.len() {
+                        if lengths[i] > max {
+                            max = lengths[i];
+                        }
+                        i += 1;
+                    }
+                    max
+                }
+                1 + max_len(&[#(#max_lengths),*])
+            }})
+        }
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            &input.ident,
+            "`MaxSerializedLen` cannot be derived for unions",
+        )),
+    }
 }
 
-fn fields_len(fields: &Fields) -> TokenStream2 {
+fn fields_len(fields: &Fields) -> syn::Result<TokenStream2> {
     match fields {
         Fields::Named(ref fields) => recourse_fields(&mut fields.named.iter()),
         Fields::Unnamed(ref fields) => recourse_fields(&mut fields.unnamed.iter()),
-        Fields::Unit => quote! { 0 },
+        Fields::Unit => Ok(quote! { 0 }),
     }
 }
 
-fn recourse_fields(fields: &mut dyn Iterator<Item = &syn::Field>) -> TokenStream2 {
-    let recourse = fields.map(|f| {
-        if let Some(len) = find_len_attr(&f.attrs) {
-            len
-        } else {
-            get_max_serialized_len(&f.ty)
-        }
-    });
-    quote! { 0 #( + #recourse)* }
+fn recourse_fields(fields: &mut dyn Iterator<Item = &syn::Field>) -> syn::Result<TokenStream2> {
+    let recourse = fields
+        .map(|f| match find_len_attr(&f.attrs)? {
+            Some(len) => Ok(len),
+            None => get_max_serialized_len(&f.ty),
+        })
+        .collect::<syn::Result<Vec<TokenStream2>>>()?;
+    Ok(quote! { 0 #( + #recourse)* })
 }
 
-fn get_max_serialized_len(ty: &syn::Type) -> TokenStream2 {
+fn get_max_serialized_len(ty: &Type) -> syn::Result<TokenStream2> {
     match ty {
-        Type::Path(ref type_path) => {
-            if let Some(ty) = type_path.path.get_ident() {
-                quote! { #ty::MAX_SERIALIZED_LEN }
-            } else {
-                let stream = type_path.to_token_stream();
-                let mut stream_vec = stream
-                    .into_iter()
-                    .map(|token| token.to_string())
-                    .collect::<Vec<String>>();
-                stream_vec.insert(1, "::".to_string());
-                stream_vec.push("::MAX_SERIALIZED_LEN".to_string());
-                let stream_string = stream_vec.into_iter().collect::<String>();
-                let stream = TokenStream2::from_str(&stream_string).unwrap();
-                quote! { #stream }
-            }
+        Type::Path(_) | Type::Array(_) | Type::Tuple(_) => {
+            Ok(quote! { <#ty as MaxSerializedLen>::MAX_SERIALIZED_LEN })
         }
-        Type::Array(type_array) => {
-            let max_serialized_len = get_max_serialized_len(&type_array.elem);
-            let len = &type_array.len;
-            quote! { #max_serialized_len * #len}
-        }
-        _ => unimplemented!(),
+        Type::Paren(type_paren) => get_max_serialized_len(&type_paren.elem),
+        Type::Group(type_group) => get_max_serialized_len(&type_group.elem),
+        Type::Reference(type_reference) => get_max_serialized_len(&type_reference.elem),
+        _ => Err(syn::Error::new_spanned(
+            ty,
+            "the maximum serialized length of this type is unknown, \
+            provide it via the `len` attribute",
+        )),
     }
 }
 
-fn find_len_attr(attrs: &[Attribute]) -> Option<TokenStream2> {
+fn find_len_attr(attrs: &[Attribute]) -> syn::Result<Option<TokenStream2>> {
     attrs
         .iter()
         .find(|attr| attr.path.is_ident("len"))
         .map(|len| {
-            let length = len.parse_args::<TokenStream2>().unwrap();
-            quote! { #length }
+            len.parse_args::<TokenStream2>()
+                .map(|length| quote! { (#length) })
         })
+        .transpose()
 }
 
-#[proc_macro_derive(AccountState)]
+/// Returns the generics of the `MaxSerializedLen` impl, i.e. the generics of
+/// the data structure with the bounds set via `#[max_len(bound = "...")]` or,
+/// if there is no such attribute, with the given bound on every type
+/// parameter whose length is computed from the fields.
+fn max_len_generics(input: &DeriveInput, bound: &TokenStream2) -> syn::Result<Generics> {
+    let mut generics = input.generics.clone();
+    let predicates = match find_bound_attr(&input.attrs)? {
+        Some(predicates) => predicates,
+        None => {
+            let mut fields = Vec::new();
+            match input.data {
+                Data::Struct(ref data) => fields.extend(data.fields.iter()),
+                Data::Enum(ref data) => {
+                    for variant in data.variants.iter() {
+                        if find_len_attr(&variant.attrs)?.is_none() {
+                            fields.extend(variant.fields.iter());
+                        }
+                    }
+                }
+                Data::Union(_) => {}
+            }
+            let mut fields_without_len = Vec::new();
+            for field in fields {
+                if find_len_attr(&field.attrs)?.is_none() {
+                    fields_without_len.push(field);
+                }
+            }
+            bound_type_params(&input.generics, &fields_without_len, bound)
+        }
+    };
+    generics.make_where_clause().predicates.extend(predicates);
+    Ok(generics)
+}
+
+/// Generates `T: <bound>` predicates for the type parameters used in the
+/// provided fields.
+fn bound_type_params(
+    generics: &Generics,
+    fields: &[&syn::Field],
+    bound: &TokenStream2,
+) -> Vec<WherePredicate> {
+    let params = generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect::<Vec<&Ident>>();
+    let mut used = Vec::<&Ident>::new();
+    for field in fields {
+        collect_type_params(&field.ty, &params, &mut used);
+    }
+    params
+        .into_iter()
+        .filter(|param| used.contains(param))
+        .map(|param| syn::parse_quote! { #param: #bound })
+        .collect()
+}
+
+/// Collects the type parameters that occur in a type. `PhantomData` is
+/// skipped because it doesn't need any bounds on its type parameter.
+fn collect_type_params<'a>(ty: &Type, params: &[&'a Ident], used: &mut Vec<&'a Ident>) {
+    match ty {
+        Type::Path(type_path) => {
+            if let Some(qself) = &type_path.qself {
+                collect_type_params(&qself.ty, params, used);
+            }
+            if let Some(first) = type_path.path.segments.first() {
+                if let Some(param) = params.iter().find(|param| **param == &first.ident) {
+                    used.push(param);
+                }
+            }
+            for segment in type_path.path.segments.iter() {
+                if segment.ident == "PhantomData" {
+                    continue;
+                }
+                if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                    for argument in arguments.args.iter() {
+                        if let GenericArgument::Type(ty) = argument {
+                            collect_type_params(ty, params, used);
+                        }
+                    }
+                }
+            }
+        }
+        Type::Array(type_array) => collect_type_params(&type_array.elem, params, used),
+        Type::Tuple(type_tuple) => type_tuple
+            .elems
+            .iter()
+            .for_each(|elem| collect_type_params(elem, params, used)),
+        Type::Paren(type_paren) => collect_type_params(&type_paren.elem, params, used),
+        Type::Group(type_group) => collect_type_params(&type_group.elem, params, used),
+        Type::Reference(type_reference) => collect_type_params(&type_reference.elem, params, used),
+        _ => {}
+    }
+}
+
+/// Parses the where-predicates of a `#[max_len(bound = "...")]` attribute.
+fn find_bound_attr(attrs: &[Attribute]) -> syn::Result<Option<Vec<WherePredicate>>> {
+    let attr = match attrs.iter().find(|attr| attr.path.is_ident("max_len")) {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+    let list = match attr.parse_meta()? {
+        Meta::List(list) => list,
+        meta => {
+            return Err(syn::Error::new_spanned(
+                meta,
+                "expected `#[max_len(bound = \"...\")]`",
+            ))
+        }
+    };
+    let mut predicates = None;
+    for nested in list.nested.iter() {
+        match nested {
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("bound") => {
+                let bound = match &name_value.lit {
+                    Lit::Str(bound) => bound,
+                    lit => return Err(syn::Error::new_spanned(lit, "expected a string literal")),
+                };
+                let parsed =
+                    bound.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+                predicates = Some(parsed.into_iter().collect());
+            }
+            _ => return Err(syn::Error::new_spanned(nested, "unknown max_len option")),
+        }
+    }
+    Ok(predicates)
+}
+
+#[proc_macro_derive(SerializedLen)]
+pub fn impl_serialized_len(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let len = match serialized_len(&input) {
+        Ok(len) => len,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let generics = inferred_generics(&input, &quote! { SerializedLen });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // evaluates the length right away, so that enums with variants of
+    // different lengths fail to compile even if the length is never used
+    // (generic types can only be checked when used)
+    let check = if input.generics.params.is_empty() {
+        quote! {
+            const _: usize = <#name as SerializedLen>::SERIALIZED_LEN;
+        }
+    } else {
+        quote! {}
+    };
+    let token_stream2 = quote! {
+        impl #impl_generics SerializedLen for #name #ty_generics #where_clause {
+            const SERIALIZED_LEN: usize = #len;
+        }
+        #check
+    };
+    token_stream2.into()
+}
+
+fn serialized_len(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    match input.data {
+        Data::Struct(ref data) => exact_fields_len(&data.fields),
+        Data::Enum(ref data) => {
+            if data.variants.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "enums without variants have no serialized length",
+                ));
+            }
+            let lengths = data
+                .variants
+                .iter()
+                .map(|variant| exact_fields_len(&variant.fields))
+                .collect::<syn::Result<Vec<TokenStream2>>>()?;
+            Ok(quote! {{
+                const fn exact_len(lengths: &[usize]) -> usize {
+                    let mut i = 1;
+                    while i < lengths.len() {
+                        if lengths[i] != lengths[0] {
+                            panic!("enum variants have different serialized lengths");
+                        }
+                        i += 1;
+                    }
+                    lengths[0]
+                }
+                1 + exact_len(&[#(#lengths),*])
+            }})
+        }
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            &input.ident,
+            "`SerializedLen` cannot be derived for unions",
+        )),
+    }
+}
+
+fn exact_fields_len(fields: &Fields) -> syn::Result<TokenStream2> {
+    let lengths = fields
+        .iter()
+        .map(|field| {
+            check_fixed_size(&field.ty)?;
+            let ty = &field.ty;
+            Ok(quote_spanned! { ty.span()=> <#ty as SerializedLen>::SERIALIZED_LEN })
+        })
+        .collect::<syn::Result<Vec<TokenStream2>>>()?;
+    Ok(quote! { 0 #( + #lengths)* })
+}
+
+/// Rejects types that are known to have a variable serialized length.
+fn check_fixed_size(ty: &Type) -> syn::Result<()> {
+    match ty {
+        Type::Path(type_path) => {
+            let segment = match type_path.path.segments.last() {
+                Some(segment) => segment,
+                None => return Ok(()),
+            };
+            match segment.ident.to_string().as_str() {
+                "Vec" | "VecDeque" | "LinkedList" | "BinaryHeap" | "String" | "Option"
+                | "BTreeMap" | "HashMap" | "BTreeSet" | "HashSet" | "MaxLenVec"
+                | "MaxLenString" | "MaxLenBTreeMap" | "MaxLenBTreeSet" | "MaxLenRingBuffer" => Err(
+                    syn::Error::new_spanned(ty, "field has a variable serialized length"),
+                ),
+                "PhantomData" => Ok(()),
+                _ => {
+                    if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                        for argument in arguments.args.iter() {
+                            if let GenericArgument::Type(ty) = argument {
+                                check_fixed_size(ty)?;
+                            }
+                        }
+                    }
+                    Ok(())
+                }
+            }
+        }
+        Type::Array(type_array) => check_fixed_size(&type_array.elem),
+        Type::Tuple(type_tuple) => type_tuple.elems.iter().try_for_each(check_fixed_size),
+        Type::Paren(type_paren) => check_fixed_size(&type_paren.elem),
+        Type::Group(type_group) => check_fixed_size(&type_group.elem),
+        Type::Reference(type_reference) => check_fixed_size(&type_reference.elem),
+        _ => Err(syn::Error::new_spanned(
+            ty,
+            "the serialized length of this type is unknown",
+        )),
+    }
+}
+
+#[proc_macro_derive(MinSerializedLen)]
+pub fn impl_min_serialized_len(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let len = match min_serialized_len(&input) {
+        Ok(len) => len,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let generics = inferred_generics(&input, &quote! { MinSerializedLen });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let token_stream2 = quote! {
+        impl #impl_generics MinSerializedLen for #name #ty_generics #where_clause {
+            const MIN_SERIALIZED_LEN: usize = #len;
+        }
+    };
+    token_stream2.into()
+}
+
+fn min_serialized_len(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    match input.data {
+        Data::Struct(ref data) => min_fields_len(&data.fields),
+        Data::Enum(ref data) => {
+            let lengths = data
+                .variants
+                .iter()
+                .map(|variant| min_fields_len(&variant.fields))
+                .collect::<syn::Result<Vec<TokenStream2>>>()?;
+            Ok(quote! {{
+                const fn min_len(lengths: &[usize]) -> usize {
+                    if lengths.is_empty() {
+                        return 0;
+                    }
+                    let mut min = lengths[0];
+                    let mut i = 1;
+                    while i < lengths.len() {
+                        if lengths[i] < min {
+                            min = lengths[i];
+                        }
+                        i += 1;
+                    }
+                    min
+                }
+                1 + min_len(&[#(#lengths),*])
+            }})
+        }
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            &input.ident,
+            "`MinSerializedLen` cannot be derived for unions",
+        )),
+    }
+}
+
+fn min_fields_len(fields: &Fields) -> syn::Result<TokenStream2> {
+    let lengths = fields
+        .iter()
+        .map(|field| match &field.ty {
+            Type::Path(_) | Type::Array(_) | Type::Tuple(_) | Type::Paren(_) | Type::Group(_) => {
+                let ty = &field.ty;
+                Ok(quote_spanned! { ty.span()=> <#ty as MinSerializedLen>::MIN_SERIALIZED_LEN })
+            }
+            Type::Reference(type_reference) => {
+                let ty = &type_reference.elem;
+                Ok(quote_spanned! { ty.span()=> <#ty as MinSerializedLen>::MIN_SERIALIZED_LEN })
+            }
+            ty => Err(syn::Error::new_spanned(
+                ty,
+                "the minimum serialized length of this type is unknown",
+            )),
+        })
+        .collect::<syn::Result<Vec<TokenStream2>>>()?;
+    Ok(quote! { 0 #( + #lengths)* })
+}
+
+/// Returns the generics of the data structure with the given bound on every
+/// type parameter used in its fields.
+fn inferred_generics(input: &DeriveInput, bound: &TokenStream2) -> Generics {
+    let fields = match input.data {
+        Data::Struct(ref data) => data.fields.iter().collect(),
+        Data::Enum(ref data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter())
+            .collect(),
+        Data::Union(_) => Vec::new(),
+    };
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .extend(bound_type_params(&input.generics, &fields, bound));
+    generics
+}
+
+#[proc_macro_derive(Accounts, attributes(account))]
+pub fn impl_accounts(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match accounts::accounts(&input) {
+        Ok(token_stream2) => token_stream2.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[proc_macro_derive(Instruction, attributes(instruction))]
+pub fn impl_instruction(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match instruction::instruction(&input) {
+        Ok(token_stream2) => token_stream2.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[proc_macro_derive(AccountState, attributes(account_state))]
 pub fn impl_account_state(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let name = &input.ident;
+    let bound = quote! { ::borsh::BorshSerialize + ::borsh::BorshDeserialize };
+    let generics = inferred_generics(&input, &bound);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let options = match AccountStateOptions::from_input(&input) {
+        Ok(options) => options,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let discriminator = options.discriminator.map(|discriminator| {
+        let discriminator = LitByteStr::new(&discriminator, Span::call_site());
+        quote! { const DISCRIMINATOR: &'static [u8] = #discriminator; }
+    });
 
     let token_stream2 = quote! {
-        impl #impl_generics AccountState for #name #ty_generics #where_clause {}
+        impl #impl_generics AccountState for #name #ty_generics #where_clause {
+            #discriminator
+        }
     };
     token_stream2.into()
 }
+
+/// Options set via the `account_state` attribute of a data structure.
+#[derive(Default)]
+struct AccountStateOptions {
+    discriminator: Option<Vec<u8>>,
+}
+
+impl AccountStateOptions {
+    fn from_input(input: &DeriveInput) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("account_state"))
+        {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => {
+                    return Err(syn::Error::new_spanned(
+                        meta,
+                        "expected `#[account_state(...)]`",
+                    ))
+                }
+            };
+            for nested in list.nested.iter() {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("discriminator") => {
+                        options.discriminator =
+                            Some(anchor_discriminator(&input.ident.to_string()));
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("discriminator") =>
+                    {
+                        let discriminator = parse_discriminator(&name_value.lit)?;
+                        // closed accounts start with 8 `0xff` bytes
+                        if discriminator.iter().take(8).all(|byte| *byte == 0xff) {
+                            return Err(syn::Error::new_spanned(
+                                &name_value.lit,
+                                "discriminator collides with the closed account discriminator",
+                            ));
+                        }
+                        options.discriminator = Some(discriminator);
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            nested,
+                            "unknown account_state option",
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(options)
+    }
+}
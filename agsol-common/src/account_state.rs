@@ -1,29 +1,141 @@
+use crate::MaxSerializedLen;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::account_info::AccountInfo;
 use solana_program::borsh::try_from_slice_unchecked;
 use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
 
 /// Something that can be read from or written into a Solana account's data
 /// field.
+///
+/// The serialized data may be prefixed with a `DISCRIMINATOR` tag that
+/// identifies the type of the account, so that an account of one type cannot
+/// be read as another. It can be set via the derive macro either as a hex or
+/// a byte string, e.g. `#[account_state(discriminator = b"pool")]`, or
+/// computed like an Anchor discriminator (the first 8 bytes of
+/// `sha256("account:<Name>")`) via `#[account_state(discriminator)]`.
+///
+/// Discriminators colliding with [`CLOSED_ACCOUNT_DISCRIMINATOR`] are
+/// rejected by the derive macro.
+///
+/// ```compile_fail
+/// # #[macro_use]
+/// # extern crate agsol_common_derive;
+/// use agsol_common::AccountState;
+/// use borsh::{BorshSerialize, BorshDeserialize};
+///
+/// #[derive(BorshSerialize, BorshDeserialize, AccountState)]
+/// #[account_state(discriminator = "0xffff")]
+/// struct Pool {
+///     amount: u64,
+/// }
+/// # fn main() {}
+/// ```
 pub trait AccountState: BorshSerialize + BorshDeserialize {
+    /// Tag prefixing the serialized data. Empty by default.
+    const DISCRIMINATOR: &'static [u8] = &[];
+
     fn read(account: &AccountInfo) -> Result<Self, ProgramError>
     where
         Self: Sized,
     {
-        let unpacked = try_from_slice_unchecked(&account.data.borrow())?;
+        let data = account.data.borrow();
+        let data = strip_discriminator(&data, Self::DISCRIMINATOR)?;
+        let unpacked = try_from_slice_unchecked(data)?;
         Ok(unpacked)
     }
     fn write(&self, account: &AccountInfo) -> Result<(), ProgramError> {
-        self.serialize(&mut &mut account.data.borrow_mut()[..])?;
+        let mut data = account.data.borrow_mut();
+        if data.len() < Self::DISCRIMINATOR.len() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let (discriminator, data) = data.split_at_mut(Self::DISCRIMINATOR.len());
+        discriminator.copy_from_slice(Self::DISCRIMINATOR);
+        self.serialize(&mut &mut data[..])?;
         Ok(())
     }
+
+    /// Reads the state after checking that the account is owned by the
+    /// program and that its data can hold the largest possible state.
+    fn read_checked(account: &AccountInfo, program_id: &Pubkey) -> Result<Self, ProgramError>
+    where
+        Self: Sized + MaxSerializedLen,
+    {
+        check_owner_and_len::<Self>(account, program_id)?;
+        Self::read(account)
+    }
+
+    /// Writes the state after checking that the account is writable, owned by
+    /// the program and that its data can hold the largest possible state.
+    fn write_checked(&self, account: &AccountInfo, program_id: &Pubkey) -> Result<(), ProgramError>
+    where
+        Self: Sized + MaxSerializedLen,
+    {
+        if !account.is_writable {
+            return Err(AccountStateError::NotWritable.into());
+        }
+        check_owner_and_len::<Self>(account, program_id)?;
+        self.write(account)
+    }
+}
+
+fn check_owner_and_len<T>(account: &AccountInfo, program_id: &Pubkey) -> Result<(), ProgramError>
+where
+    T: AccountState + MaxSerializedLen,
+{
+    if account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if account.data_len() < T::DISCRIMINATOR.len() + T::MAX_SERIALIZED_LEN {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    Ok(())
+}
+
+/// Discriminator written into accounts closed via
+/// [`close_account`](crate::close_account).
+pub const CLOSED_ACCOUNT_DISCRIMINATOR: [u8; 8] = [0xff; 8];
+
+/// Strips the discriminator from the account data. Data that has been marked
+/// as closed is reported as such, even if the discriminator happens to be a
+/// prefix of the closed marker.
+pub(crate) fn strip_discriminator<'a>(
+    data: &'a [u8],
+    discriminator: &[u8],
+) -> Result<&'a [u8], AccountStateError> {
+    // without a discriminator the state itself may start with `0xff` bytes
+    if !discriminator.is_empty() && data.starts_with(&CLOSED_ACCOUNT_DISCRIMINATOR) {
+        return Err(AccountStateError::AccountClosed);
+    }
+    data.strip_prefix(discriminator)
+        .ok_or(AccountStateError::InvalidDiscriminator)
+}
+
+/// Offset of the custom `ProgramError` codes returned by this crate.
+pub const ACCOUNT_STATE_ERROR_OFFSET: u32 = 0xa650_0000;
+
+/// Errors returned when reading or writing an [`AccountState`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum AccountStateError {
+    #[error("Account discriminator mismatch")]
+    InvalidDiscriminator,
+    #[error("Account is not writable")]
+    NotWritable,
+    #[error("Unsupported account state version")]
+    UnsupportedVersion,
+    #[error("Account is closed")]
+    AccountClosed,
+}
+
+impl From<AccountStateError> for ProgramError {
+    fn from(error: AccountStateError) -> Self {
+        ProgramError::Custom(ACCOUNT_STATE_ERROR_OFFSET + error as u32)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::MaxSerializedLen;
-    use solana_program::pubkey::Pubkey;
     use std::cell::RefCell;
     use std::rc::Rc;
 
@@ -79,4 +191,227 @@ mod test {
         assert_eq!(dummy.e, SomeEnum::B(15436));
         assert_eq!(dummy.e, some_enum);
     }
+
+    #[derive(BorshSerialize, BorshDeserialize, AccountState)]
+    struct GenericDummy<T> {
+        a: T,
+        b: Option<T>,
+    }
+
+    #[test]
+    fn read_write_generic_account() {
+        let mut lamports = 45;
+        let mut data = [0_u8; 5];
+
+        let account_info = AccountInfo {
+            key: &Pubkey::new_unique(),
+            is_signer: true,
+            is_writable: true,
+            lamports: Rc::new(RefCell::new(&mut lamports)),
+            data: Rc::new(RefCell::new(&mut data)),
+            owner: &Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 4432,
+        };
+
+        let dummy = GenericDummy::<u16> {
+            a: 0xa9f4,
+            b: Some(12),
+        };
+        dummy.write(&account_info).unwrap();
+        let read_dummy = GenericDummy::<u16>::read(&account_info).unwrap();
+        assert_eq!(read_dummy.a, dummy.a);
+        assert_eq!(read_dummy.b, dummy.b);
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, AccountState, Debug, PartialEq)]
+    #[account_state(discriminator = b"pool")]
+    struct Pool {
+        a: u32,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, AccountState, Debug, PartialEq)]
+    #[account_state(discriminator)]
+    struct Vault {
+        a: u32,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, AccountState, Debug, PartialEq)]
+    #[account_state(discriminator = "0x01ff")]
+    struct Tagged {
+        a: u32,
+    }
+
+    #[test]
+    fn discriminators() {
+        assert_eq!(Pool::DISCRIMINATOR, b"pool");
+        assert_eq!(
+            Vault::DISCRIMINATOR,
+            &solana_program::hash::hash(b"account:Vault").to_bytes()[..8]
+        );
+        assert_eq!(Tagged::DISCRIMINATOR, &[0x01, 0xff]);
+        assert!(Dummy::DISCRIMINATOR.is_empty());
+
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 45;
+        let mut data = [0_u8; 12];
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            data.as_mut_slice(),
+            &owner,
+            false,
+            0,
+        );
+
+        let vault = Vault { a: 0xdeadbeef };
+        vault.write(&account_info).unwrap();
+        assert_eq!(&account_info.data.borrow()[..8], Vault::DISCRIMINATOR);
+        assert_eq!(Vault::read(&account_info).unwrap(), vault);
+        // type confusion is caught
+        assert_eq!(
+            Pool::read(&account_info),
+            Err(AccountStateError::InvalidDiscriminator.into())
+        );
+
+        let pool = Pool { a: 12 };
+        pool.write(&account_info).unwrap();
+        assert_eq!(
+            &account_info.data.borrow()[..8],
+            &[b'p', b'o', b'o', b'l', 12, 0, 0, 0]
+        );
+        assert_eq!(Pool::read(&account_info).unwrap(), pool);
+        assert_eq!(
+            Vault::read(&account_info),
+            Err(ProgramError::Custom(ACCOUNT_STATE_ERROR_OFFSET))
+        );
+
+        let mut data = [0_u8; 1];
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            data.as_mut_slice(),
+            &owner,
+            false,
+            0,
+        );
+        assert_eq!(
+            Tagged { a: 1 }.write(&account_info),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+        assert_eq!(
+            Tagged::read(&account_info),
+            Err(AccountStateError::InvalidDiscriminator.into())
+        );
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct Marked {
+        a: u32,
+    }
+
+    // bypasses the derive macro rejecting such discriminators
+    impl AccountState for Marked {
+        const DISCRIMINATOR: &'static [u8] = b"\xff\xff";
+    }
+
+    #[test]
+    fn closed_marker_prefix() {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 45;
+        let mut data = [0_u8; 12];
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            data.as_mut_slice(),
+            &owner,
+            false,
+            0,
+        );
+
+        let marked = Marked { a: 7 };
+        marked.write(&account_info).unwrap();
+        assert_eq!(Marked::read(&account_info).unwrap(), marked);
+
+        account_info.data.borrow_mut()[..8].copy_from_slice(&CLOSED_ACCOUNT_DISCRIMINATOR);
+        assert_eq!(
+            Marked::read(&account_info),
+            Err(AccountStateError::AccountClosed.into())
+        );
+    }
+
+    #[test]
+    fn checked_read_write() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 45;
+        let mut data = [0_u8; 3 + MAX_ENUM_LEN];
+        let mut account_info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            data.as_mut_slice(),
+            &program_id,
+            false,
+            0,
+        );
+
+        let dummy = Dummy {
+            a: 1,
+            b: 2,
+            e: SomeEnum::A,
+        };
+        dummy.write_checked(&account_info, &program_id).unwrap();
+        let read_dummy = Dummy::read_checked(&account_info, &program_id).unwrap();
+        assert_eq!(read_dummy.b, 2);
+
+        // wrong owner
+        let other_program_id = Pubkey::new_unique();
+        assert_eq!(
+            Dummy::read_checked(&account_info, &other_program_id).err(),
+            Some(ProgramError::IllegalOwner)
+        );
+        assert_eq!(
+            dummy.write_checked(&account_info, &other_program_id),
+            Err(ProgramError::IllegalOwner)
+        );
+
+        // not writable
+        account_info.is_writable = false;
+        assert_eq!(
+            dummy.write_checked(&account_info, &program_id),
+            Err(AccountStateError::NotWritable.into())
+        );
+        assert!(Dummy::read_checked(&account_info, &program_id).is_ok());
+
+        // data too small for the largest possible state
+        let mut data = [0_u8; 3 + MAX_ENUM_LEN - 1];
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            data.as_mut_slice(),
+            &program_id,
+            false,
+            0,
+        );
+        assert_eq!(
+            Dummy::read_checked(&account_info, &program_id).err(),
+            Some(ProgramError::AccountDataTooSmall)
+        );
+        assert_eq!(
+            dummy.write_checked(&account_info, &program_id),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+    }
 }
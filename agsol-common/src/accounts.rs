@@ -0,0 +1,169 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+/// A list of accounts expected by an instruction, parsed from the account
+/// infos passed to the program.
+///
+/// Deriving it for a struct of `&'a AccountInfo<'info>` fields takes the
+/// accounts in the order of the fields and checks the constraints given in
+/// their `account` attributes:
+///
+/// - `signer`: the account signed the transaction
+/// - `mut`: the account is writable
+/// - `owner = <expr>`: the account is owned by the given program
+/// - `seeds = [<expr>, ...]`: the account is the PDA of the program with the
+///   given seeds, checked via [`SignerPda`](crate::SignerPda)
+/// - `rent_exempt`: the account holds enough lamports to be rent exempt
+///
+/// Constraint expressions may refer to `program_id` and to any of the
+/// preceding fields. Failing checks log the name of the offending account
+/// before returning the corresponding [`ProgramError`].
+///
+/// Note that the returned errors are the bare [`ProgramError`] variants
+/// (e.g. [`ProgramError::MissingRequiredSignature`]), so clients can only
+/// tell which account failed a check from the transaction logs.
+///
+/// # Examples
+/// ```rust
+/// # #[macro_use]
+/// # extern crate agsol_common_derive;
+/// use agsol_common::Accounts;
+/// use solana_program::account_info::AccountInfo;
+/// use solana_program::entrypoint::ProgramResult;
+/// use solana_program::pubkey::Pubkey;
+/// use solana_program::system_program;
+///
+/// #[derive(Accounts)]
+/// struct Deposit<'a, 'info> {
+///     #[account(signer, mut)]
+///     payer: &'a AccountInfo<'info>,
+///     #[account(mut, owner = program_id, seeds = [b"vault", payer.key.as_ref()])]
+///     vault: &'a AccountInfo<'info>,
+///     #[account(owner = &system_program::ID)]
+///     system_program: &'a AccountInfo<'info>,
+/// }
+///
+/// fn process_deposit(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+///     let accounts = Deposit::from_account_infos(program_id, accounts)?;
+///     // ...
+///     # let _ = accounts.vault;
+///     Ok(())
+/// }
+/// # fn main() {}
+/// ```
+pub trait Accounts<'a, 'info>: Sized {
+    fn from_account_infos(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::AccountStateError;
+    use solana_program::system_program;
+
+    #[derive(Accounts)]
+    struct Deposit<'a, 'info> {
+        #[account(signer, mut)]
+        payer: &'a AccountInfo<'info>,
+        #[account(mut, owner = program_id, seeds = [b"vault", payer.key])]
+        vault: &'a AccountInfo<'info>,
+        #[account(owner = &system_program::ID)]
+        system_program: &'a AccountInfo<'info>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Accounts)]
+    struct RentExempt<'a> {
+        #[account(rent_exempt)]
+        account: &'a AccountInfo<'a>,
+    }
+
+    #[test]
+    fn parse_accounts() {
+        let program_id = Pubkey::new_unique();
+        let payer_key = Pubkey::new_unique();
+        let (vault_key, _) =
+            Pubkey::find_program_address(&[b"vault", payer_key.as_ref()], &program_id);
+        let system_program_id = system_program::ID;
+        let mut lamports = [1, 1, 1];
+        let mut payer_data = [];
+        let mut vault_data = [0_u8; 8];
+        let mut system_program_data = [];
+        let [payer_lamports, vault_lamports, system_program_lamports] = &mut lamports;
+        let payer = AccountInfo::new(
+            &payer_key,
+            true,
+            true,
+            payer_lamports,
+            &mut payer_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let vault = AccountInfo::new(
+            &vault_key,
+            false,
+            true,
+            vault_lamports,
+            &mut vault_data,
+            &program_id,
+            false,
+            0,
+        );
+        let system_program = AccountInfo::new(
+            &system_program_id,
+            false,
+            false,
+            system_program_lamports,
+            &mut system_program_data,
+            &system_program_id,
+            true,
+            0,
+        );
+        let mut accounts = vec![payer, vault, system_program];
+
+        let deposit = Deposit::from_account_infos(&program_id, &accounts).unwrap();
+        assert_eq!(deposit.payer.key, &payer_key);
+        assert_eq!(deposit.vault.key, &vault_key);
+        assert_eq!(deposit.system_program.key, &system_program_id);
+
+        assert_eq!(
+            Deposit::from_account_infos(&program_id, &accounts[..2]).err(),
+            Some(ProgramError::NotEnoughAccountKeys)
+        );
+        assert_eq!(
+            Deposit::from_account_infos(&Pubkey::new_unique(), &accounts).err(),
+            Some(ProgramError::IllegalOwner)
+        );
+
+        accounts.swap(0, 1);
+        assert_eq!(
+            Deposit::from_account_infos(&program_id, &accounts).err(),
+            Some(ProgramError::MissingRequiredSignature)
+        );
+        accounts.swap(0, 1);
+
+        accounts[1].is_writable = false;
+        assert_eq!(
+            Deposit::from_account_infos(&program_id, &accounts).err(),
+            Some(AccountStateError::NotWritable.into())
+        );
+        accounts[1].is_writable = true;
+
+        accounts[0].key = &system_program_id;
+        assert_eq!(
+            Deposit::from_account_infos(&program_id, &accounts).err(),
+            Some(ProgramError::InvalidSeeds)
+        );
+
+        // the rent sysvar is only available on-chain
+        assert_eq!(
+            RentExempt::from_account_infos(&program_id, &accounts).err(),
+            Some(ProgramError::UnsupportedSysvar)
+        );
+    }
+}
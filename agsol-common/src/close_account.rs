@@ -0,0 +1,148 @@
+use crate::account_state::strip_discriminator;
+use crate::{AccountState, AccountStateError, CLOSED_ACCOUNT_DISCRIMINATOR};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+
+/// Closes a program owned account holding a `T` by draining its lamports
+/// into the recipient and zeroing its data.
+///
+/// An account without lamports is garbage collected at the end of the
+/// transaction, however, it can be revived until then by funding it again.
+/// To guard against that, the account is reassigned to the system program,
+/// so that it cannot be used as a program account within the same
+/// transaction.
+///
+/// If `mark_closed` is set, [`CLOSED_ACCOUNT_DISCRIMINATOR`] is written into
+/// the data instead, so that later reads of types with a discriminator fail
+/// with [`AccountStateError::AccountClosed`]. Since the runtime only allows
+/// reassigning accounts with zeroed data, such accounts remain owned by the
+/// program.
+pub fn close_account<T: AccountState>(
+    account: &AccountInfo,
+    recipient: &AccountInfo,
+    program_id: &Pubkey,
+    mark_closed: bool,
+) -> ProgramResult {
+    if account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if !account.is_writable || !recipient.is_writable {
+        return Err(AccountStateError::NotWritable.into());
+    }
+    if account.key == recipient.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    strip_discriminator(&account.data.borrow(), T::DISCRIMINATOR)?;
+    if mark_closed && account.data_len() < CLOSED_ACCOUNT_DISCRIMINATOR.len() {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+
+    let recipient_lamports = recipient
+        .lamports()
+        .checked_add(account.lamports())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    **recipient.lamports.borrow_mut() = recipient_lamports;
+    **account.lamports.borrow_mut() = 0;
+
+    let mut data = account.data.borrow_mut();
+    data.fill(0);
+    if mark_closed {
+        data[..CLOSED_ACCOUNT_DISCRIMINATOR.len()].copy_from_slice(&CLOSED_ACCOUNT_DISCRIMINATOR);
+    } else {
+        account.assign(&system_program::ID);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[derive(BorshSerialize, BorshDeserialize, AccountState, Debug, PartialEq)]
+    #[account_state(discriminator = b"vault")]
+    struct Vault {
+        amount: u64,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, AccountState, Debug, PartialEq)]
+    #[account_state(discriminator = b"pool")]
+    struct Pool {
+        amount: u64,
+    }
+
+    #[test]
+    fn close_accounts() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let recipient_key = Pubkey::new_unique();
+        let vault = Vault { amount: 10 };
+
+        for mark_closed in [false, true] {
+            let owner = program_id;
+            let mut lamports = 1000;
+            let mut data = [0_u8; 5 + 8 + 3];
+            let account = AccountInfo::new(
+                &key,
+                false,
+                true,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+            let mut recipient_lamports = 500;
+            let mut recipient_data = [];
+            let recipient = AccountInfo::new(
+                &recipient_key,
+                false,
+                true,
+                &mut recipient_lamports,
+                &mut recipient_data,
+                &system_program::ID,
+                false,
+                0,
+            );
+            vault.write(&account).unwrap();
+
+            // wrong type and owner
+            assert_eq!(
+                close_account::<Pool>(&account, &recipient, &program_id, mark_closed),
+                Err(AccountStateError::InvalidDiscriminator.into())
+            );
+            assert_eq!(
+                close_account::<Vault>(&account, &recipient, &Pubkey::new_unique(), mark_closed),
+                Err(ProgramError::IllegalOwner)
+            );
+            assert_eq!(account.lamports(), 1000);
+
+            close_account::<Vault>(&account, &recipient, &program_id, mark_closed).unwrap();
+            assert_eq!(account.lamports(), 0);
+            assert_eq!(recipient.lamports(), 1500);
+            if mark_closed {
+                assert_eq!(account.owner, &program_id);
+                assert_eq!(account.data.borrow()[..8], CLOSED_ACCOUNT_DISCRIMINATOR);
+                assert!(account.data.borrow()[8..].iter().all(|byte| *byte == 0));
+                assert_eq!(
+                    Vault::read(&account),
+                    Err(AccountStateError::AccountClosed.into())
+                );
+                assert_eq!(
+                    close_account::<Vault>(&account, &recipient, &program_id, mark_closed),
+                    Err(AccountStateError::AccountClosed.into())
+                );
+            } else {
+                assert_eq!(account.owner, &system_program::ID);
+                assert!(account.data.borrow().iter().all(|byte| *byte == 0));
+                assert_eq!(
+                    Vault::read(&account),
+                    Err(AccountStateError::InvalidDiscriminator.into())
+                );
+            }
+        }
+    }
+}
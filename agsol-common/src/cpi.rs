@@ -0,0 +1,417 @@
+//! Cross-program invocations signed by a [`SignerPda`].
+//!
+//! System program calls are always available, SPL token program calls are
+//! enabled by the `spl-token` feature.
+
+use crate::SignerPda;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use solana_program::system_program;
+
+/// Transfers lamports from a system owned PDA. Checks that `from` is the
+/// PDA.
+pub fn transfer<'a>(
+    from: &AccountInfo<'a>,
+    to: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    signer_pda: &SignerPda,
+    lamports: u64,
+) -> ProgramResult {
+    check_system_accounts(system_program, from, signer_pda)?;
+    invoke_signed(
+        &system_instruction::transfer(from.key, to.key, lamports),
+        &[from.clone(), to.clone(), system_program.clone()],
+        &[&signer_pda.signer_seeds()],
+    )
+}
+
+/// Creates a new account at the address of the PDA, funded by the payer.
+/// Checks that `new_account` is the PDA.
+pub fn create_account<'a>(
+    payer: &AccountInfo<'a>,
+    new_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    signer_pda: &SignerPda,
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+) -> ProgramResult {
+    check_system_accounts(system_program, new_account, signer_pda)?;
+    invoke_signed(
+        &system_instruction::create_account(payer.key, new_account.key, lamports, space, owner),
+        &[payer.clone(), new_account.clone(), system_program.clone()],
+        &[&signer_pda.signer_seeds()],
+    )
+}
+
+fn check_system_accounts(
+    system_program: &AccountInfo,
+    pda_account: &AccountInfo,
+    signer_pda: &SignerPda,
+) -> ProgramResult {
+    if system_program.key != &system_program::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if pda_account.key != &signer_pda.pda {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(())
+}
+
+/// SPL token program calls with a PDA authority.
+///
+/// Besides the token program id, every call checks that the authority is the
+/// PDA and that the token accounts and mints involved are owned by the token
+/// program.
+#[cfg(feature = "spl-token")]
+pub mod token {
+    use super::*;
+
+    /// Transfers tokens from an account owned by the PDA.
+    pub fn transfer<'a>(
+        source: &AccountInfo<'a>,
+        destination: &AccountInfo<'a>,
+        authority: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>,
+        signer_pda: &SignerPda,
+        amount: u64,
+    ) -> ProgramResult {
+        check_token_accounts(token_program, authority, signer_pda, &[source, destination])?;
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                source.key,
+                destination.key,
+                authority.key,
+                &[],
+                amount,
+            )?,
+            &[
+                source.clone(),
+                destination.clone(),
+                authority.clone(),
+                token_program.clone(),
+            ],
+            &[&signer_pda.signer_seeds()],
+        )
+    }
+
+    /// Mints tokens of a mint whose authority is the PDA.
+    pub fn mint_to<'a>(
+        mint: &AccountInfo<'a>,
+        destination: &AccountInfo<'a>,
+        authority: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>,
+        signer_pda: &SignerPda,
+        amount: u64,
+    ) -> ProgramResult {
+        check_token_accounts(token_program, authority, signer_pda, &[mint, destination])?;
+        invoke_signed(
+            &spl_token::instruction::mint_to(
+                token_program.key,
+                mint.key,
+                destination.key,
+                authority.key,
+                &[],
+                amount,
+            )?,
+            &[
+                mint.clone(),
+                destination.clone(),
+                authority.clone(),
+                token_program.clone(),
+            ],
+            &[&signer_pda.signer_seeds()],
+        )
+    }
+
+    /// Burns tokens from an account owned by the PDA.
+    pub fn burn<'a>(
+        account: &AccountInfo<'a>,
+        mint: &AccountInfo<'a>,
+        authority: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>,
+        signer_pda: &SignerPda,
+        amount: u64,
+    ) -> ProgramResult {
+        check_token_accounts(token_program, authority, signer_pda, &[account, mint])?;
+        invoke_signed(
+            &spl_token::instruction::burn(
+                token_program.key,
+                account.key,
+                mint.key,
+                authority.key,
+                &[],
+                amount,
+            )?,
+            &[
+                account.clone(),
+                mint.clone(),
+                authority.clone(),
+                token_program.clone(),
+            ],
+            &[&signer_pda.signer_seeds()],
+        )
+    }
+
+    /// Closes a token account owned by the PDA, sending its lamports to the
+    /// destination.
+    pub fn close_account<'a>(
+        account: &AccountInfo<'a>,
+        destination: &AccountInfo<'a>,
+        authority: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>,
+        signer_pda: &SignerPda,
+    ) -> ProgramResult {
+        check_token_accounts(token_program, authority, signer_pda, &[account])?;
+        invoke_signed(
+            &spl_token::instruction::close_account(
+                token_program.key,
+                account.key,
+                destination.key,
+                authority.key,
+                &[],
+            )?,
+            &[
+                account.clone(),
+                destination.clone(),
+                authority.clone(),
+                token_program.clone(),
+            ],
+            &[&signer_pda.signer_seeds()],
+        )
+    }
+
+    fn check_token_accounts(
+        token_program: &AccountInfo,
+        authority: &AccountInfo,
+        signer_pda: &SignerPda,
+        accounts: &[&AccountInfo],
+    ) -> ProgramResult {
+        spl_token::check_program_account(token_program.key)?;
+        if authority.key != &signer_pda.pda {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if accounts
+            .iter()
+            .any(|account| account.owner != token_program.key)
+        {
+            return Err(ProgramError::IllegalOwner);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn program_checks() {
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"vault"];
+        let (pda, bump) = Pubkey::find_program_address(seeds, &program_id);
+        let signer_pda = SignerPda {
+            pda,
+            bump: [bump],
+            seeds,
+        };
+        let to_key = Pubkey::new_unique();
+        let fake_program_id = Pubkey::new_unique();
+        let system_program_id = system_program::ID;
+        let mut lamports = [100, 0, 1, 1];
+        let mut data = [[0_u8; 0]; 4];
+        let [pda_lamports, to_lamports, system_lamports, fake_lamports] = &mut lamports;
+        let [pda_data, to_data, system_data, fake_data] = &mut data;
+        let from = AccountInfo::new(
+            &pda,
+            false,
+            true,
+            pda_lamports,
+            pda_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let to = AccountInfo::new(
+            &to_key,
+            false,
+            true,
+            to_lamports,
+            to_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let system_program = AccountInfo::new(
+            &system_program_id,
+            false,
+            false,
+            system_lamports,
+            system_data,
+            &system_program_id,
+            true,
+            0,
+        );
+        let fake_program = AccountInfo::new(
+            &fake_program_id,
+            false,
+            false,
+            fake_lamports,
+            fake_data,
+            &system_program_id,
+            true,
+            0,
+        );
+
+        assert!(transfer(&from, &to, &system_program, &signer_pda, 10).is_ok());
+        assert_eq!(
+            transfer(&from, &to, &fake_program, &signer_pda, 10),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        assert_eq!(
+            create_account(&to, &from, &fake_program, &signer_pda, 10, 0, &program_id),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        assert!(
+            create_account(&to, &from, &system_program, &signer_pda, 10, 0, &program_id).is_ok()
+        );
+        // the signing account is not the PDA
+        assert_eq!(
+            transfer(&to, &from, &system_program, &signer_pda, 10),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            create_account(&from, &to, &system_program, &signer_pda, 10, 0, &program_id),
+            Err(ProgramError::InvalidSeeds)
+        );
+
+        #[cfg(feature = "spl-token")]
+        {
+            // not the token program
+            assert_eq!(
+                token::transfer(&from, &to, &from, &system_program, &signer_pda, 10),
+                Err(ProgramError::IncorrectProgramId)
+            );
+        }
+    }
+
+    #[cfg(feature = "spl-token")]
+    #[test]
+    fn token_checks() {
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"vault"];
+        let (pda, bump) = Pubkey::find_program_address(seeds, &program_id);
+        let signer_pda = SignerPda {
+            pda,
+            bump: [bump],
+            seeds,
+        };
+        let token_program_id = spl_token::id();
+        let system_program_id = system_program::ID;
+        let source_key = Pubkey::new_unique();
+        let destination_key = Pubkey::new_unique();
+        let other_key = Pubkey::new_unique();
+        let mut lamports = [1; 5];
+        let mut data = [[0_u8; 0]; 5];
+        let [source_lamports, destination_lamports, authority_lamports, other_lamports, token_lamports] =
+            &mut lamports;
+        let [source_data, destination_data, authority_data, other_data, token_data] = &mut data;
+        let source = AccountInfo::new(
+            &source_key,
+            false,
+            true,
+            source_lamports,
+            source_data,
+            &token_program_id,
+            false,
+            0,
+        );
+        let destination = AccountInfo::new(
+            &destination_key,
+            false,
+            true,
+            destination_lamports,
+            destination_data,
+            &token_program_id,
+            false,
+            0,
+        );
+        let authority = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            authority_lamports,
+            authority_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        // not owned by the token program
+        let other = AccountInfo::new(
+            &other_key,
+            false,
+            true,
+            other_lamports,
+            other_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let token_program = AccountInfo::new(
+            &token_program_id,
+            false,
+            false,
+            token_lamports,
+            token_data,
+            &system_program_id,
+            true,
+            0,
+        );
+
+        assert!(token::transfer(
+            &source,
+            &destination,
+            &authority,
+            &token_program,
+            &signer_pda,
+            10
+        )
+        .is_ok());
+        assert_eq!(
+            token::transfer(&source, &other, &authority, &token_program, &signer_pda, 10),
+            Err(ProgramError::IllegalOwner)
+        );
+        assert_eq!(
+            token::burn(
+                &other,
+                &destination,
+                &authority,
+                &token_program,
+                &signer_pda,
+                10
+            ),
+            Err(ProgramError::IllegalOwner)
+        );
+        // the authority is not the PDA
+        assert_eq!(
+            token::mint_to(
+                &source,
+                &destination,
+                &other,
+                &token_program,
+                &signer_pda,
+                10
+            ),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            token::close_account(&source, &destination, &source, &token_program, &signer_pda),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use crate::{AccountState, MaxSerializedLen, SignerPda};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::Instruction;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+/// Creates a rent exempt PDA account owned by the program that is large
+/// enough to hold the largest possible `T` and writes the initial state into
+/// it.
+///
+/// Accounts that have already been funded (e.g. by someone sending lamports
+/// to the address in advance) cannot be created via `create_account`,
+/// therefore they are topped up to the rent exempt balance, allocated and
+/// assigned to the program instead.
+pub fn create_pda_account<'a, T>(
+    state: &T,
+    pda_account: &AccountInfo<'a>,
+    signer_pda: &SignerPda,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+) -> ProgramResult
+where
+    T: AccountState + MaxSerializedLen,
+{
+    let rent = Rent::get()?;
+    create_pda_account_with_rent(
+        state,
+        pda_account,
+        signer_pda,
+        payer,
+        system_program,
+        program_id,
+        &rent,
+    )
+}
+
+fn create_pda_account_with_rent<'a, T>(
+    state: &T,
+    pda_account: &AccountInfo<'a>,
+    signer_pda: &SignerPda,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    rent: &Rent,
+) -> ProgramResult
+where
+    T: AccountState + MaxSerializedLen,
+{
+    if pda_account.key != &signer_pda.pda {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if pda_account.owner != &system_program::ID {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let space = T::DISCRIMINATOR.len() + T::MAX_SERIALIZED_LEN;
+    let signer_seeds = signer_pda.signer_seeds();
+    for instruction in create_pda_account_instructions(
+        payer.key,
+        pda_account.key,
+        pda_account.lamports(),
+        space,
+        program_id,
+        rent,
+    ) {
+        invoke_signed(
+            &instruction,
+            &[payer.clone(), pda_account.clone(), system_program.clone()],
+            &[&signer_seeds],
+        )?;
+    }
+
+    state.write(pda_account)
+}
+
+/// Returns the system program instructions creating the account, depending
+/// on whether it has already been funded.
+fn create_pda_account_instructions(
+    payer: &Pubkey,
+    pda: &Pubkey,
+    lamports: u64,
+    space: usize,
+    program_id: &Pubkey,
+    rent: &Rent,
+) -> Vec<Instruction> {
+    let required_lamports = rent.minimum_balance(space);
+    if lamports == 0 {
+        return vec![system_instruction::create_account(
+            payer,
+            pda,
+            required_lamports,
+            space as u64,
+            program_id,
+        )];
+    }
+
+    let mut instructions = Vec::with_capacity(3);
+    if lamports < required_lamports {
+        instructions.push(system_instruction::transfer(
+            payer,
+            pda,
+            required_lamports - lamports,
+        ));
+    }
+    instructions.push(system_instruction::allocate(pda, space as u64));
+    instructions.push(system_instruction::assign(pda, program_id));
+    instructions
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[derive(
+        BorshSerialize, BorshDeserialize, MaxSerializedLen, AccountState, Debug, PartialEq,
+    )]
+    #[account_state(discriminator = b"pool")]
+    struct Pool {
+        authority: Pubkey,
+        amount: u64,
+    }
+
+    #[test]
+    fn instructions_depend_on_funding() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let pda = Pubkey::new_unique();
+        let rent = Rent::default();
+        let required_lamports = rent.minimum_balance(44);
+
+        assert_eq!(
+            create_pda_account_instructions(&payer, &pda, 0, 44, &program_id, &rent),
+            vec![system_instruction::create_account(
+                &payer,
+                &pda,
+                required_lamports,
+                44,
+                &program_id
+            )]
+        );
+        assert_eq!(
+            create_pda_account_instructions(&payer, &pda, 1000, 44, &program_id, &rent),
+            vec![
+                system_instruction::transfer(&payer, &pda, required_lamports - 1000),
+                system_instruction::allocate(&pda, 44),
+                system_instruction::assign(&pda, &program_id),
+            ]
+        );
+        assert_eq!(
+            create_pda_account_instructions(
+                &payer,
+                &pda,
+                required_lamports,
+                44,
+                &program_id,
+                &rent
+            ),
+            vec![
+                system_instruction::allocate(&pda, 44),
+                system_instruction::assign(&pda, &program_id),
+            ]
+        );
+    }
+
+    #[test]
+    fn create_pda_accounts() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"pool", authority.as_ref()];
+        let (pda, _) = Pubkey::find_program_address(seeds, &program_id);
+        let system_program_id = system_program::ID;
+        let payer_key = Pubkey::new_unique();
+        // fresh and prefunded accounts (the CPIs are no-ops off-chain, see
+        // above for the instructions of each case)
+        let mut lamports = [0, 1000, 0];
+        let mut data = [[0_u8; 4 + 40]; 3];
+        let (last_lamports, lamports) = lamports.split_last_mut().unwrap();
+        let (last_data, data) = data.split_last_mut().unwrap();
+
+        let mut payer_lamports = 1_000_000_000;
+        let mut payer_data = [];
+        let payer = AccountInfo::new(
+            &payer_key,
+            true,
+            true,
+            &mut payer_lamports,
+            &mut payer_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let mut system_program_lamports = 1;
+        let mut system_program_data = [];
+        let system_program = AccountInfo::new(
+            &system_program_id,
+            false,
+            false,
+            &mut system_program_lamports,
+            &mut system_program_data,
+            &system_program_id,
+            true,
+            0,
+        );
+
+        let pool = Pool {
+            authority,
+            amount: 100,
+        };
+        for (lamports, data) in lamports.iter_mut().zip(data.iter_mut()) {
+            let pda_account = AccountInfo::new(
+                &pda,
+                false,
+                true,
+                lamports,
+                data,
+                &system_program_id,
+                false,
+                0,
+            );
+            let signer_pda = SignerPda::new_checked(seeds, &program_id, &pda_account).unwrap();
+            create_pda_account_with_rent(
+                &pool,
+                &pda_account,
+                &signer_pda,
+                &payer,
+                &system_program,
+                &program_id,
+                &Rent::default(),
+            )
+            .unwrap();
+            assert_eq!(Pool::read(&pda_account).unwrap(), pool);
+        }
+
+        let mut pda_account = AccountInfo::new(
+            &pda,
+            false,
+            true,
+            last_lamports,
+            last_data,
+            &system_program_id,
+            false,
+            0,
+        );
+        let signer_pda = SignerPda::new_checked(seeds, &program_id, &pda_account).unwrap();
+
+        // not the account of the PDA
+        let other_key = Pubkey::new_unique();
+        let mut other_account = pda_account.clone();
+        other_account.key = &other_key;
+        assert_eq!(
+            create_pda_account_with_rent(
+                &pool,
+                &other_account,
+                &signer_pda,
+                &payer,
+                &system_program,
+                &program_id,
+                &Rent::default(),
+            ),
+            Err(ProgramError::InvalidSeeds)
+        );
+
+        // already owned by the program
+        pda_account.owner = &program_id;
+        assert_eq!(
+            create_pda_account_with_rent(
+                &pool,
+                &pda_account,
+                &signer_pda,
+                &payer,
+                &system_program,
+                &program_id,
+                &Rent::default(),
+            ),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+    }
+}
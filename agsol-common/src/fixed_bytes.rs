@@ -0,0 +1,433 @@
+use super::{MaxLenString, MaxSerializedLen, MinSerializedLen, SerializedLen, CONTENTS_FULL};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::{Error, ErrorKind, Write};
+use std::str::{FromStr, Utf8Error};
+
+/// Byte buffer of at most `N` bytes stored inline.
+///
+/// The layout is the length (`u32`, little endian) followed by all `N` bytes
+/// of the buffer, without any padding, so it can be used in zero-copy account
+/// layouts (see the `bytemuck` feature). The borsh encoding is the same as the
+/// in-memory layout, thus it always serializes into `4 + N` bytes.
+///
+/// The bytes past the length are kept zeroed but ignored when comparing. Every
+/// byte pattern is a valid value: a length over `N`, e.g. in a corrupted
+/// account, is treated as `N`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FixedBytes<const N: usize> {
+    len: [u8; 4],
+    bytes: [u8; N],
+}
+
+impl<const N: usize> SerializedLen for FixedBytes<N> {
+    const SERIALIZED_LEN: usize = 4 + N;
+}
+
+impl<const N: usize> MaxSerializedLen for FixedBytes<N> {
+    const MAX_SERIALIZED_LEN: usize = 4 + N;
+}
+
+impl<const N: usize> MinSerializedLen for FixedBytes<N> {
+    const MIN_SERIALIZED_LEN: usize = 4 + N;
+}
+
+impl<const N: usize> FixedBytes<N> {
+    pub const fn new() -> Self {
+        Self {
+            len: [0; 4],
+            bytes: [0; N],
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        (u32::from_le_bytes(self.len) as usize).min(N)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len()]
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        let len = self.len();
+        &mut self.bytes[..len]
+    }
+
+    pub fn try_extend_from_slice(&mut self, slice: &[u8]) -> Result<(), &'static str> {
+        let len = self.len();
+        if slice.len() > N - len {
+            return Err(CONTENTS_FULL);
+        }
+        self.bytes[len..len + slice.len()].copy_from_slice(slice);
+        self.len = ((len + slice.len()) as u32).to_le_bytes();
+        Ok(())
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.bytes[len..].fill(0);
+            self.len = (len as u32).to_le_bytes();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<const N: usize> Default for FixedBytes<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> PartialEq for FixedBytes<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<const N: usize> Eq for FixedBytes<N> {}
+
+impl<const N: usize> Hash for FixedBytes<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_bytes().fmt(f)
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for FixedBytes<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for FixedBytes<N> {
+    type Error = &'static str;
+
+    fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+        let mut bytes = Self::new();
+        bytes.try_extend_from_slice(slice)?;
+        Ok(bytes)
+    }
+}
+
+impl<const N: usize> From<FixedBytes<N>> for Vec<u8> {
+    fn from(bytes: FixedBytes<N>) -> Self {
+        bytes.as_bytes().to_vec()
+    }
+}
+
+impl<const N: usize> BorshSerialize for FixedBytes<N> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.len)?;
+        writer.write_all(&self.bytes)
+    }
+}
+
+impl<const N: usize> BorshDeserialize for FixedBytes<N> {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let len = u32::deserialize(buf)?;
+        if len as usize > N {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("length {} exceeds the capacity {}", len, N),
+            ));
+        }
+        if buf.len() < N {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "unexpected end of fixed bytes",
+            ));
+        }
+        let (bytes, rest) = buf.split_at(N);
+        *buf = rest;
+        // the bytes past the length are dropped to keep them zeroed
+        let mut fixed = Self::new();
+        fixed.bytes[..len as usize].copy_from_slice(&bytes[..len as usize]);
+        fixed.len = len.to_le_bytes();
+        Ok(fixed)
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl<const N: usize> bytemuck::Zeroable for FixedBytes<N> {}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl<const N: usize> bytemuck::Pod for FixedBytes<N> {}
+
+/// UTF-8 string of at most `N` bytes stored inline.
+///
+/// It has the same layout and borsh encoding as [`FixedBytes`].
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FixedString<const N: usize> {
+    bytes: FixedBytes<N>,
+}
+
+impl<const N: usize> SerializedLen for FixedString<N> {
+    const SERIALIZED_LEN: usize = 4 + N;
+}
+
+impl<const N: usize> MaxSerializedLen for FixedString<N> {
+    const MAX_SERIALIZED_LEN: usize = 4 + N;
+}
+
+impl<const N: usize> MinSerializedLen for FixedString<N> {
+    const MIN_SERIALIZED_LEN: usize = 4 + N;
+}
+
+impl<const N: usize> FixedString<N> {
+    pub const fn new() -> Self {
+        Self {
+            bytes: FixedBytes::new(),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the length of the string in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns the string, failing if the contents are not valid UTF-8,
+    /// which is only possible if the string was cast from arbitrary bytes.
+    pub fn try_as_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.bytes.as_bytes())
+    }
+
+    /// Returns the string, or its longest valid UTF-8 prefix if the contents
+    /// are invalid (see [`Self::try_as_str`]).
+    pub fn as_str(&self) -> &str {
+        let bytes = self.bytes.as_bytes();
+        match std::str::from_utf8(bytes) {
+            Ok(string) => string,
+            Err(err) => std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap_or_default(),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_bytes()
+    }
+
+    pub fn try_push_str(&mut self, string: &str) -> Result<(), &'static str> {
+        self.bytes.try_extend_from_slice(string.as_bytes())
+    }
+
+    pub fn try_push(&mut self, c: char) -> Result<(), &'static str> {
+        self.try_push_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        String::from_utf8_lossy(self.as_bytes()).fmt(f)
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        String::from_utf8_lossy(self.as_bytes()).fmt(f)
+    }
+}
+
+impl<const N: usize> FromStr for FixedString<N> {
+    type Err = &'static str;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let mut fixed = Self::new();
+        fixed.try_push_str(string)?;
+        Ok(fixed)
+    }
+}
+
+impl<const N: usize> AsRef<str> for FixedString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for FixedString<N> {
+    type Error = &'static str;
+
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        string.parse()
+    }
+}
+
+impl<const N: usize> From<FixedString<N>> for String {
+    fn from(string: FixedString<N>) -> Self {
+        String::from_utf8_lossy(string.as_bytes()).into_owned()
+    }
+}
+
+impl<const N: usize, const M: usize> TryFrom<&MaxLenString<M>> for FixedString<N> {
+    type Error = &'static str;
+
+    fn try_from(string: &MaxLenString<M>) -> Result<Self, Self::Error> {
+        string.contents().parse()
+    }
+}
+
+impl<const N: usize, const M: usize> TryFrom<FixedString<N>> for MaxLenString<M> {
+    type Error = &'static str;
+
+    fn try_from(string: FixedString<N>) -> Result<Self, Self::Error> {
+        Self::try_from(string.try_as_str().map_err(|_| "invalid utf-8")?)
+    }
+}
+
+impl<const N: usize> BorshSerialize for FixedString<N> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.bytes.serialize(writer)
+    }
+}
+
+impl<const N: usize> BorshDeserialize for FixedString<N> {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let bytes = FixedBytes::deserialize(buf)?;
+        std::str::from_utf8(bytes.as_bytes())
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        Ok(Self { bytes })
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl<const N: usize> bytemuck::Zeroable for FixedString<N> {}
+
+// NOTE casting arbitrary bytes may result in invalid UTF-8, which is checked
+// by every method reading the string
+#[cfg(feature = "bytemuck")]
+unsafe impl<const N: usize> bytemuck::Pod for FixedString<N> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type TestBytes = FixedBytes<5>;
+    type TestString = FixedString<5>;
+
+    #[test]
+    fn fixed_bytes() {
+        let mut bytes = TestBytes::try_from([1, 2, 3].as_slice()).unwrap();
+        assert_eq!(bytes.as_bytes(), &[1, 2, 3]);
+        assert_eq!(bytes.try_extend_from_slice(&[4, 5, 6]), Err(CONTENTS_FULL));
+        bytes.try_extend_from_slice(&[4, 5]).unwrap();
+        assert!(bytes.is_full());
+        bytes.as_bytes_mut()[0] = 9;
+        bytes.truncate(2);
+        assert_eq!(Vec::from(bytes), vec![9, 2]);
+        assert_eq!(bytes, TestBytes::try_from([9, 2].as_slice()).unwrap());
+        assert!(TestBytes::try_from([0; 6].as_slice()).is_err());
+
+        let serialized = bytes.try_to_vec().unwrap();
+        assert_eq!(serialized, vec![2, 0, 0, 0, 9, 2, 0, 0, 0]);
+        assert_eq!(serialized.len(), TestBytes::SERIALIZED_LEN);
+        assert_eq!(TestBytes::try_from_slice(&serialized).unwrap(), bytes);
+
+        // length over capacity
+        assert!(TestBytes::try_from_slice(&[6, 0, 0, 0, 1, 2, 3, 4, 5]).is_err());
+        // truncated
+        assert!(TestBytes::try_from_slice(&[2, 0, 0, 0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn fixed_string() {
+        let mut string = TestString::from_str("ab").unwrap();
+        string.try_push('é').unwrap();
+        assert_eq!(string.len(), 4);
+        assert_eq!(string.try_push('é'), Err(CONTENTS_FULL));
+        string.try_push_str("c").unwrap();
+        assert_eq!(string.to_string(), "abéc");
+        assert_eq!(format!("{:?}", string), "\"abéc\"");
+        assert!("abcdef".parse::<TestString>().is_err());
+
+        let max_len_string = MaxLenString::<8>::try_from(string).unwrap();
+        assert_eq!(max_len_string.contents(), "abéc");
+        assert_eq!(TestString::try_from(&max_len_string), Ok(string));
+        assert!(MaxLenString::<4>::try_from(string).is_err());
+        let long_string = MaxLenString::<8>::try_from("abcdef").unwrap();
+        assert!(TestString::try_from(&long_string).is_err());
+
+        let serialized = string.try_to_vec().unwrap();
+        assert_eq!(serialized.len(), TestString::MAX_SERIALIZED_LEN);
+        assert_eq!(TestString::try_from_slice(&serialized).unwrap(), string);
+        // invalid utf-8
+        assert!(TestString::try_from_slice(&[2, 0, 0, 0, 0xc3, 0x28, 0, 0, 0]).is_err());
+
+        string.clear();
+        assert!(string.is_empty());
+        assert_eq!(string, TestString::default());
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn zero_copy() {
+        let string = TestString::from_str("abc").unwrap();
+        let bytes: &[u8] = bytemuck::bytes_of(&string);
+        assert_eq!(bytes, string.try_to_vec().unwrap().as_slice());
+        let cast: &TestString = bytemuck::from_bytes(bytes);
+        assert_eq!(cast.as_str(), "abc");
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn corrupted_cast() {
+        // length over capacity
+        let mut raw = [200, 0, 0, 0, b'a', b'b', b'c', b'd', b'e'];
+        let bytes: &mut TestBytes = bytemuck::from_bytes_mut(&mut raw);
+        assert_eq!(bytes.len(), 5);
+        assert_eq!(bytes.as_bytes(), b"abcde");
+        assert!(bytes.is_full());
+        assert_eq!(bytes.try_extend_from_slice(&[1]), Err(CONTENTS_FULL));
+        bytes.truncate(2);
+        assert_eq!(bytes.as_bytes(), b"ab");
+
+        // invalid utf-8 and a length over capacity
+        let raw = [7, 0, 0, 0, b'a', 0xc3, 0x28, b'b', b'c'];
+        let string: &TestString = bytemuck::from_bytes(&raw);
+        assert_eq!(string.len(), 5);
+        assert!(string.try_as_str().is_err());
+        assert_eq!(string.as_str(), "a");
+        assert_eq!(string.as_ref(), "a");
+        assert_eq!(string.to_string(), "a\u{fffd}(bc");
+        assert_eq!(String::from(*string), "a\u{fffd}(bc");
+        assert!(format!("{:?}", string).starts_with('"'));
+        assert_eq!(string, string);
+        assert!(MaxLenString::<8>::try_from(*string).is_err());
+        assert!(TestString::try_from_slice(&raw).is_err());
+    }
+}
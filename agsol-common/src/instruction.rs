@@ -0,0 +1,110 @@
+use solana_program::program_error::ProgramError;
+
+/// Offset of the custom `ProgramError` codes returned when dispatching
+/// instructions.
+pub const INSTRUCTION_ERROR_OFFSET: u32 = 0xa650_0100;
+
+/// Errors returned by the `process` dispatcher generated by the
+/// [`Instruction`](crate::Instruction) derive macro.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum InstructionError {
+    #[error("Unknown instruction")]
+    UnknownInstruction,
+    #[error("Invalid instruction data")]
+    InvalidInstructionData,
+}
+
+impl From<InstructionError> for ProgramError {
+    fn from(error: InstructionError) -> Self {
+        ProgramError::Custom(INSTRUCTION_ERROR_OFFSET + error as u32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use solana_program::account_info::AccountInfo;
+    use solana_program::entrypoint::ProgramResult;
+    use solana_program::pubkey::Pubkey;
+
+    #[derive(BorshSerialize, BorshDeserialize, Instruction)]
+    #[instruction(log)]
+    enum TestInstruction {
+        Initialize {
+            amount: u64,
+            fee: u16,
+        },
+        #[instruction(handler = handlers::update)]
+        UpdateFee(u16),
+        Close,
+        NFTMint,
+    }
+
+    fn process_initialize(_: &Pubkey, _: &[AccountInfo], amount: u64, fee: u16) -> ProgramResult {
+        Err(ProgramError::Custom(amount as u32 + fee as u32))
+    }
+
+    fn process_close(_: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        Err(ProgramError::Custom(accounts.len() as u32))
+    }
+
+    fn process_nft_mint(_: &Pubkey, _: &[AccountInfo]) -> ProgramResult {
+        Err(ProgramError::Custom(42))
+    }
+
+    mod handlers {
+        use super::*;
+
+        pub fn update(_: &Pubkey, _: &[AccountInfo], fee: u16) -> ProgramResult {
+            Err(ProgramError::Custom(fee as u32))
+        }
+    }
+
+    #[test]
+    fn dispatch_instructions() {
+        let program_id = Pubkey::new_unique();
+        let process =
+            |instruction_data: &[u8]| TestInstruction::process(&program_id, &[], instruction_data);
+
+        let initialize = TestInstruction::Initialize {
+            amount: 10,
+            fee: 20,
+        };
+        assert_eq!(
+            process(&initialize.try_to_vec().unwrap()),
+            Err(ProgramError::Custom(30))
+        );
+        assert_eq!(
+            process(&TestInstruction::UpdateFee(5).try_to_vec().unwrap()),
+            Err(ProgramError::Custom(5))
+        );
+        assert_eq!(
+            process(&TestInstruction::Close.try_to_vec().unwrap()),
+            Err(ProgramError::Custom(0))
+        );
+        assert_eq!(
+            process(&TestInstruction::NFTMint.try_to_vec().unwrap()),
+            Err(ProgramError::Custom(42))
+        );
+
+        // unknown instructions
+        assert_eq!(
+            process(&[]),
+            Err(InstructionError::UnknownInstruction.into())
+        );
+        assert_eq!(
+            process(&[4]),
+            Err(InstructionError::UnknownInstruction.into())
+        );
+        // truncated and trailing data
+        assert_eq!(
+            process(&[0, 10, 0, 0]),
+            Err(InstructionError::InvalidInstructionData.into())
+        );
+        assert_eq!(
+            process(&[2, 0]),
+            Err(InstructionError::InvalidInstructionData.into())
+        );
+    }
+}
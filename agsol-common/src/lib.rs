@@ -1,27 +1,117 @@
+// lets the generated code of derive macros refer to `::agsol_common` within
+// this crate as well
+extern crate self as agsol_common;
+
 mod account_state;
+mod accounts;
+mod close_account;
+pub mod cpi;
+mod create_account;
+mod fixed_bytes;
+mod instruction;
 mod locked_item;
 mod max_len_btree;
+mod max_len_ring_buffer;
 mod max_len_string;
 mod max_len_vec;
 mod max_serialized_len;
+mod seeds;
+mod serialized_len;
 mod signer_pda;
 mod timelock;
+mod versioned_account_state;
 
-pub use account_state::AccountState;
+pub use account_state::{
+    AccountState, AccountStateError, ACCOUNT_STATE_ERROR_OFFSET, CLOSED_ACCOUNT_DISCRIMINATOR,
+};
+pub use accounts::Accounts;
+pub use close_account::close_account;
+pub use create_account::create_pda_account;
+pub use fixed_bytes::{FixedBytes, FixedString};
+pub use instruction::{InstructionError, INSTRUCTION_ERROR_OFFSET};
 pub use locked_item::LockedItem;
-pub use max_len_btree::MaxLenBTreeMap;
+pub use max_len_btree::{MaxLenBTreeEntry, MaxLenBTreeMap, MaxLenBTreeSet, MaxLenBTreeVacantEntry};
+pub use max_len_ring_buffer::MaxLenRingBuffer;
 pub use max_len_string::MaxLenString;
 pub use max_len_vec::MaxLenVec;
 pub use max_serialized_len::MaxSerializedLen;
-pub use signer_pda::{SignerPda, SignerPdaError};
+pub use serialized_len::{MinSerializedLen, SerializedLen};
+pub use signer_pda::{OwnedSignerPda, SignerPda, SignerPdaError};
 pub use timelock::{Timelock, TimelockError};
+pub use versioned_account_state::VersionedAccountState;
 
 #[cfg(any(test, feature = "derive"))]
 #[allow(unused_imports)]
 #[macro_use]
 extern crate agsol_common_derive;
+/// Derives a `process(program_id, accounts, instruction_data)` dispatcher
+/// for an instruction enum.
+///
+/// The generated function decodes the instruction and calls the handler of
+/// the variant with `program_id`, `accounts` and the fields of the variant.
+/// The handler of a variant defaults to `process_<variant_in_snake_case>`
+/// (e.g. `process_nft_mint` for `NFTMint`) and can be set via
+/// `#[instruction(handler = path::to::handler)]`. Every instruction is
+/// logged via `msg!` if the enum is annotated with `#[instruction(log)]`,
+/// single instructions can be logged by annotating their variant instead.
+/// Decoding failures are reported as [`InstructionError`]s.
+///
+/// # Examples
+/// ```rust
+/// # #[macro_use]
+/// # extern crate agsol_common_derive;
+/// use borsh::{BorshDeserialize, BorshSerialize};
+/// use solana_program::account_info::AccountInfo;
+/// use solana_program::entrypoint::ProgramResult;
+/// use solana_program::pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize, BorshDeserialize, Instruction)]
+/// enum PoolInstruction {
+///     #[instruction(log)]
+///     Deposit { amount: u64 },
+///     NFTMint,
+///     #[instruction(handler = close)]
+///     Close,
+/// }
+///
+/// fn process_deposit(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+///     Ok(())
+/// }
+///
+/// fn process_nft_mint(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+///     Ok(())
+/// }
+///
+/// fn close(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+///     Ok(())
+/// }
+///
+/// fn process_instruction(
+///     program_id: &Pubkey,
+///     accounts: &[AccountInfo],
+///     instruction_data: &[u8],
+/// ) -> ProgramResult {
+///     PoolInstruction::process(program_id, accounts, instruction_data)
+/// }
+/// # fn main() {}
+/// ```
+#[cfg(any(test, feature = "derive"))]
+pub use agsol_common_derive::Instruction;
 #[cfg(any(test, feature = "derive"))]
 pub use agsol_common_derive::*;
 
 const CONTENTS_FULL: &str = "contents full";
 type MaxLenResult = Result<(), &'static str>;
+
+/// Reads the length prefix of a borsh encoded collection, rejecting lengths
+/// over the capacity `N` before anything is allocated.
+fn deserialize_max_len<const N: usize>(buf: &mut &[u8]) -> std::io::Result<usize> {
+    let len = <u32 as borsh::BorshDeserialize>::deserialize(buf)? as usize;
+    if len > N {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("length {} exceeds the capacity {}", len, N),
+        ));
+    }
+    Ok(len)
+}
@@ -1,4 +1,4 @@
-use super::MaxSerializedLen;
+use super::{MaxSerializedLen, MinSerializedLen, SerializedLen};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::clock::UnixTimestamp;
 use std::cmp::Ordering;
@@ -16,12 +16,26 @@ where
     const MAX_SERIALIZED_LEN: usize = T::MAX_SERIALIZED_LEN + UnixTimestamp::MAX_SERIALIZED_LEN;
 }
 
+impl<T> SerializedLen for LockedItem<T>
+where
+    T: BorshSerialize + BorshDeserialize + MaxSerializedLen + SerializedLen,
+{
+    const SERIALIZED_LEN: usize = T::SERIALIZED_LEN + UnixTimestamp::SERIALIZED_LEN;
+}
+
+impl<T> MinSerializedLen for LockedItem<T>
+where
+    T: BorshSerialize + BorshDeserialize + MaxSerializedLen + MinSerializedLen,
+{
+    const MIN_SERIALIZED_LEN: usize = T::MIN_SERIALIZED_LEN + UnixTimestamp::MIN_SERIALIZED_LEN;
+}
+
 impl<T> PartialOrd for LockedItem<T>
 where
     T: BorshSerialize + BorshDeserialize + MaxSerializedLen,
 {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.expires.cmp(&other.expires))
+        Some(self.cmp(other))
     }
 }
 
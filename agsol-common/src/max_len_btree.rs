@@ -1,14 +1,15 @@
-use super::{MaxSerializedLen, CONTENTS_FULL};
+use super::{deserialize_max_len, MaxSerializedLen, MinSerializedLen, CONTENTS_FULL};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::borrow::Borrow;
+use std::collections::{btree_map, btree_set, BTreeMap, BTreeSet};
 use std::convert::TryFrom;
 use std::hash::Hash;
+use std::ops::RangeBounds;
 
 #[repr(C)]
-#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[derive(BorshSerialize, Clone, Debug, PartialEq, Eq)]
 pub struct MaxLenBTreeMap<K, V, const N: usize>
 where
     K: MaxSerializedLen + Clone + Ord + Hash,
@@ -25,6 +26,14 @@ where
     const MAX_SERIALIZED_LEN: usize = 4 + N * (K::MAX_SERIALIZED_LEN + V::MAX_SERIALIZED_LEN);
 }
 
+impl<K, V, const N: usize> MinSerializedLen for MaxLenBTreeMap<K, V, N>
+where
+    K: MaxSerializedLen + Clone + Ord + Hash,
+    V: MaxSerializedLen + Clone,
+{
+    const MIN_SERIALIZED_LEN: usize = 4;
+}
+
 impl<K, V, const N: usize> MaxLenBTreeMap<K, V, N>
 where
     K: MaxSerializedLen + Clone + Ord + Hash,
@@ -49,10 +58,22 @@ where
     }
 
     pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, &'static str> {
-        if self.contents.keys().any(|k| k.cmp(&key) == Ordering::Equal) || !self.is_full() {
-            Ok(self.contents.insert(key, value))
-        } else {
+        if self.is_full() && !self.contents.contains_key(&key) {
             Err(CONTENTS_FULL)
+        } else {
+            Ok(self.contents.insert(key, value))
+        }
+    }
+
+    /// Gets the entry of the key for in-place manipulation. Inserting into a
+    /// vacant entry fails if the map is full.
+    pub fn entry(&mut self, key: K) -> MaxLenBTreeEntry<'_, K, V> {
+        let full = self.is_full();
+        match self.contents.entry(key) {
+            btree_map::Entry::Occupied(entry) => MaxLenBTreeEntry::Occupied(entry),
+            btree_map::Entry::Vacant(entry) => {
+                MaxLenBTreeEntry::Vacant(MaxLenBTreeVacantEntry { entry, full })
+            }
         }
     }
 
@@ -79,6 +100,130 @@ where
     pub fn contents(&self) -> &BTreeMap<K, V> {
         &self.contents
     }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, K, V> {
+        self.contents.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> btree_map::IterMut<'_, K, V> {
+        self.contents.iter_mut()
+    }
+
+    pub fn keys(&self) -> btree_map::Keys<'_, K, V> {
+        self.contents.keys()
+    }
+
+    pub fn values(&self) -> btree_map::Values<'_, K, V> {
+        self.contents.values()
+    }
+
+    pub fn values_mut(&mut self) -> btree_map::ValuesMut<'_, K, V> {
+        self.contents.values_mut()
+    }
+
+    pub fn range<T, R>(&self, range: R) -> btree_map::Range<'_, K, V>
+    where
+        T: Ord + ?Sized,
+        K: Borrow<T>,
+        R: RangeBounds<T>,
+    {
+        self.contents.range(range)
+    }
+
+    pub fn range_mut<T, R>(&mut self, range: R) -> btree_map::RangeMut<'_, K, V>
+    where
+        T: Ord + ?Sized,
+        K: Borrow<T>,
+        R: RangeBounds<T>,
+    {
+        self.contents.range_mut(range)
+    }
+
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.contents.retain(f);
+    }
+
+    // the std `first_key_value`/`pop_first` family needs Rust 1.66
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.contents.iter().next()
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.contents.iter().next_back()
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let key = self.contents.keys().next()?.clone();
+        self.contents.remove_entry(&key)
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let key = self.contents.keys().next_back()?.clone();
+        self.contents.remove_entry(&key)
+    }
+}
+
+/// Entry of a [`MaxLenBTreeMap`].
+pub enum MaxLenBTreeEntry<'a, K, V> {
+    Occupied(btree_map::OccupiedEntry<'a, K, V>),
+    Vacant(MaxLenBTreeVacantEntry<'a, K, V>),
+}
+
+/// Vacant entry of a [`MaxLenBTreeMap`] that can only be filled if the map
+/// isn't full.
+pub struct MaxLenBTreeVacantEntry<'a, K, V> {
+    entry: btree_map::VacantEntry<'a, K, V>,
+    full: bool,
+}
+
+impl<'a, K: Ord, V> MaxLenBTreeVacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    pub fn insert(self, value: V) -> Result<&'a mut V, &'static str> {
+        if self.full {
+            Err(CONTENTS_FULL)
+        } else {
+            Ok(self.entry.insert(value))
+        }
+    }
+}
+
+impl<'a, K: Ord, V> MaxLenBTreeEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Self::Occupied(entry) => entry.key(),
+            Self::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Self::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    pub fn or_try_insert(self, default: V) -> Result<&'a mut V, &'static str> {
+        self.or_try_insert_with(|| default)
+    }
+
+    pub fn or_try_insert_with<F>(self, default: F) -> Result<&'a mut V, &'static str>
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Self::Occupied(entry) => Ok(entry.into_mut()),
+            Self::Vacant(entry) => entry.insert(default()),
+        }
+    }
 }
 
 impl<K, V, const N: usize> TryFrom<BTreeMap<K, V>> for MaxLenBTreeMap<K, V, N>
@@ -106,6 +251,219 @@ where
     }
 }
 
+impl<K, V, const N: usize> BorshDeserialize for MaxLenBTreeMap<K, V, N>
+where
+    K: MaxSerializedLen + Clone + Ord + Hash + BorshDeserialize,
+    V: MaxSerializedLen + Clone + BorshDeserialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let len = deserialize_max_len::<N>(buf)?;
+        let mut contents = BTreeMap::new();
+        for _ in 0..len {
+            let key = K::deserialize(buf)?;
+            let value = V::deserialize(buf)?;
+            contents.insert(key, value);
+        }
+        Ok(Self { contents })
+    }
+}
+
+impl<K, V, const N: usize> IntoIterator for MaxLenBTreeMap<K, V, N>
+where
+    K: MaxSerializedLen + Clone + Ord + Hash,
+    V: MaxSerializedLen + Clone,
+{
+    type Item = (K, V);
+    type IntoIter = btree_map::IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contents.into_iter()
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a MaxLenBTreeMap<K, V, N>
+where
+    K: MaxSerializedLen + Clone + Ord + Hash,
+    V: MaxSerializedLen + Clone,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = btree_map::Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contents.iter()
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, Clone, Debug, PartialEq, Eq)]
+pub struct MaxLenBTreeSet<T, const N: usize>
+where
+    T: MaxSerializedLen + Clone + Ord + Hash,
+{
+    contents: BTreeSet<T>,
+}
+
+impl<T, const N: usize> MaxSerializedLen for MaxLenBTreeSet<T, N>
+where
+    T: MaxSerializedLen + Clone + Ord + Hash,
+{
+    const MAX_SERIALIZED_LEN: usize = 4 + N * T::MAX_SERIALIZED_LEN;
+}
+
+impl<T, const N: usize> MinSerializedLen for MaxLenBTreeSet<T, N>
+where
+    T: MaxSerializedLen + Clone + Ord + Hash,
+{
+    const MIN_SERIALIZED_LEN: usize = 4;
+}
+
+impl<T, const N: usize> MaxLenBTreeSet<T, N>
+where
+    T: MaxSerializedLen + Clone + Ord + Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            contents: BTreeSet::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.contents.len() == N
+    }
+
+    /// Adds a value to the set, returning whether it was newly inserted.
+    pub fn insert(&mut self, value: T) -> Result<bool, &'static str> {
+        if self.is_full() && !self.contents.contains(&value) {
+            Err(CONTENTS_FULL)
+        } else {
+            Ok(self.contents.insert(value))
+        }
+    }
+
+    pub fn remove(&mut self, value: &T) -> bool {
+        self.contents.remove(value)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.contents.contains(value)
+    }
+
+    pub fn clear(&mut self) {
+        self.contents.clear();
+    }
+
+    pub fn contents(&self) -> &BTreeSet<T> {
+        &self.contents
+    }
+
+    pub fn iter(&self) -> btree_set::Iter<'_, T> {
+        self.contents.iter()
+    }
+
+    pub fn range<U, R>(&self, range: R) -> btree_set::Range<'_, T>
+    where
+        U: Ord + ?Sized,
+        T: Borrow<U>,
+        R: RangeBounds<U>,
+    {
+        self.contents.range(range)
+    }
+
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.contents.retain(f);
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.contents.iter().next()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.contents.iter().next_back()
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        let value = self.contents.iter().next()?.clone();
+        self.contents.take(&value)
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        let value = self.contents.iter().next_back()?.clone();
+        self.contents.take(&value)
+    }
+}
+
+impl<T, const N: usize> TryFrom<BTreeSet<T>> for MaxLenBTreeSet<T, N>
+where
+    T: MaxSerializedLen + Clone + Ord + Hash,
+{
+    type Error = &'static str;
+
+    fn try_from(btree: BTreeSet<T>) -> Result<Self, Self::Error> {
+        if btree.len() > N {
+            return Err(CONTENTS_FULL);
+        }
+        Ok(Self { contents: btree })
+    }
+}
+
+impl<T, const N: usize> Default for MaxLenBTreeSet<T, N>
+where
+    T: MaxSerializedLen + Clone + Ord + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> BorshDeserialize for MaxLenBTreeSet<T, N>
+where
+    T: MaxSerializedLen + Clone + Ord + Hash + BorshDeserialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let len = deserialize_max_len::<N>(buf)?;
+        let mut contents = BTreeSet::new();
+        for _ in 0..len {
+            contents.insert(T::deserialize(buf)?);
+        }
+        Ok(Self { contents })
+    }
+}
+
+impl<T, const N: usize> IntoIterator for MaxLenBTreeSet<T, N>
+where
+    T: MaxSerializedLen + Clone + Ord + Hash,
+{
+    type Item = T;
+    type IntoIter = btree_set::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contents.into_iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a MaxLenBTreeSet<T, N>
+where
+    T: MaxSerializedLen + Clone + Ord + Hash,
+{
+    type Item = &'a T;
+    type IntoIter = btree_set::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contents.iter()
+    }
+}
+
 #[cfg(test)]
 mod test_max_len_btreemap {
     use super::*;
@@ -158,4 +516,109 @@ mod test_max_len_btreemap {
         // re-insert into full map
         assert_eq!(test_btree.insert(3_u8, 4), Ok(Some(3)));
     }
+
+    #[test]
+    fn map_api() {
+        let mut map = TestBTree::new();
+        for i in 0..5 {
+            map.insert(i * 2, i as u32).unwrap();
+        }
+        assert_eq!(
+            map.range(2..6).map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+        for value in map.values_mut() {
+            *value *= 10;
+        }
+        assert_eq!(
+            map.values().copied().collect::<Vec<_>>(),
+            vec![0, 10, 20, 30, 40]
+        );
+        assert_eq!(map.first_key_value(), Some((&0, &0)));
+        assert_eq!(map.last_key_value(), Some((&8, &40)));
+        assert_eq!(map.pop_last(), Some((8, 40)));
+        assert_eq!(map.pop_first(), Some((0, 0)));
+
+        // entries
+        *map.entry(2).or_try_insert(0).unwrap() += 1;
+        assert_eq!(map.get(&2), Some(&11));
+        map.entry(3)
+            .and_modify(|v| *v = 100)
+            .or_try_insert(3)
+            .unwrap();
+        assert_eq!(map.get(&3), Some(&3));
+        map.insert(5, 50).unwrap();
+        assert!(map.is_full());
+        assert_eq!(map.entry(9).or_try_insert(9), Err(CONTENTS_FULL));
+        assert_eq!(map.entry(3).and_modify(|v| *v += 1).key(), &3);
+        assert_eq!(map.get(&3), Some(&4));
+
+        map.retain(|k, _| k % 2 == 0);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![2, 4, 6]);
+        assert_eq!(
+            map.into_iter().collect::<Vec<_>>(),
+            vec![(2, 11), (4, 20), (6, 30)]
+        );
+    }
+
+    #[test]
+    fn set_api() {
+        type TestSet = MaxLenBTreeSet<u16, 3>;
+        assert_eq!(TestSet::MAX_SERIALIZED_LEN, 4 + 3 * 2);
+
+        let mut set = TestSet::new();
+        assert_eq!(set.insert(5), Ok(true));
+        assert_eq!(set.insert(1), Ok(true));
+        assert_eq!(set.insert(5), Ok(false));
+        assert_eq!(set.insert(3), Ok(true));
+        assert_eq!(set.insert(4), Err(CONTENTS_FULL));
+        assert_eq!(set.insert(3), Ok(false));
+        assert_eq!(set.try_to_vec().unwrap().len(), TestSet::MAX_SERIALIZED_LEN);
+        assert_eq!(
+            TestSet::try_from_slice(&set.try_to_vec().unwrap()).unwrap(),
+            set
+        );
+
+        assert_eq!(set.range(2..).copied().collect::<Vec<_>>(), vec![3, 5]);
+        assert_eq!(set.first(), Some(&1));
+        assert_eq!(set.last(), Some(&5));
+        assert_eq!(set.pop_last(), Some(5));
+        set.retain(|v| *v > 1);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![3]);
+        assert!(set.contains(&3));
+        assert!(set.remove(&3));
+        assert!(set.is_empty());
+        assert_eq!(set.pop_first(), None);
+        assert_eq!(set.last(), None);
+
+        assert!(TestSet::try_from((0..4).collect::<BTreeSet<_>>()).is_err());
+    }
+
+    #[test]
+    fn deserialization() {
+        let mut map = TestBTree::new();
+        map.insert(1, 10).unwrap();
+        let bytes = map.try_to_vec().unwrap();
+        assert_eq!(bytes, vec![1, 0, 0, 0, 1, 10, 0, 0, 0]);
+        assert_eq!(TestBTree::try_from_slice(&bytes).unwrap(), map);
+
+        // 6 entries
+        let mut bytes = vec![6, 0, 0, 0];
+        for key in 0..6 {
+            bytes.extend_from_slice(&[key, 0, 0, 0, 0]);
+        }
+        assert!(TestBTree::try_from_slice(&bytes).is_err());
+        // huge length prefix without the data
+        assert!(TestBTree::try_from_slice(&[255, 255, 255, 255]).is_err());
+
+        type TestSet = MaxLenBTreeSet<u8, 2>;
+        assert_eq!(
+            TestSet::try_from_slice(&[2, 0, 0, 0, 1, 2])
+                .unwrap()
+                .contents()
+                .len(),
+            2
+        );
+        assert!(TestSet::try_from_slice(&[3, 0, 0, 0, 1, 2, 3]).is_err());
+    }
 }
@@ -0,0 +1,206 @@
+use super::{deserialize_max_len, MaxSerializedLen, MinSerializedLen};
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::io::{Error, ErrorKind};
+
+/// Fixed-capacity ring buffer that overwrites its oldest element when full.
+///
+/// Unlike [`MaxLenVec::cyclic_push`](crate::MaxLenVec::cyclic_push), pushing
+/// is O(1). The serialized layout is the index of the oldest element (`u32`)
+/// followed by the contents in storage order (a borsh `Vec`).
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct MaxLenRingBuffer<T, const N: usize> {
+    head: u32,
+    contents: Vec<T>,
+}
+
+impl<T, const N: usize> MaxSerializedLen for MaxLenRingBuffer<T, N>
+where
+    T: MaxSerializedLen,
+{
+    const MAX_SERIALIZED_LEN: usize = 4 + 4 + N * T::MAX_SERIALIZED_LEN;
+}
+
+impl<T, const N: usize> MinSerializedLen for MaxLenRingBuffer<T, N> {
+    const MIN_SERIALIZED_LEN: usize = 8;
+}
+
+impl<T, const N: usize> MaxLenRingBuffer<T, N> {
+    pub fn new() -> Self {
+        Self {
+            head: 0,
+            contents: Vec::with_capacity(N),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.contents.len() == N
+    }
+
+    /// Pushes a new element, returning the oldest one if it was overwritten.
+    pub fn push(&mut self, elem: T) -> Option<T> {
+        if N == 0 {
+            return Some(elem);
+        }
+        if self.is_full() {
+            let head = self.head as usize;
+            self.head = ((head + 1) % N) as u32;
+            Some(std::mem::replace(&mut self.contents[head], elem))
+        } else {
+            self.contents.push(elem);
+            None
+        }
+    }
+
+    /// Returns the most recently pushed element.
+    pub fn latest(&self) -> Option<&T> {
+        if self.is_empty() {
+            None
+        } else {
+            let index = (self.head as usize + self.len() - 1) % self.len();
+            Some(&self.contents[index])
+        }
+    }
+
+    /// Returns the oldest element.
+    pub fn oldest(&self) -> Option<&T> {
+        self.contents.get(self.head as usize)
+    }
+
+    /// Returns the element at `index`, counting from the oldest one.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len() {
+            Some(&self.contents[(self.head as usize + index) % self.len()])
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over the elements from the oldest to the latest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        let (newer, older) = self.contents.split_at(self.head as usize);
+        older.iter().chain(newer.iter())
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.contents.clear();
+    }
+}
+
+impl<T, const N: usize> Default for MaxLenRingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for MaxLenRingBuffer<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for MaxLenRingBuffer<T, N> {}
+
+impl<T: Clone, const N: usize> From<MaxLenRingBuffer<T, N>> for Vec<T> {
+    fn from(buffer: MaxLenRingBuffer<T, N>) -> Self {
+        buffer.iter().cloned().collect()
+    }
+}
+
+impl<T, const N: usize> BorshDeserialize for MaxLenRingBuffer<T, N>
+where
+    T: BorshDeserialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let head = u32::deserialize(buf)?;
+        let len = deserialize_max_len::<N>(buf)?;
+        let mut contents = Vec::with_capacity(len);
+        for _ in 0..len {
+            contents.push(T::deserialize(buf)?);
+        }
+        // the head only moves once the buffer is full
+        if head != 0 && (contents.len() < N || head as usize >= N) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "invalid ring buffer head",
+            ));
+        }
+        Ok(Self { head, contents })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type TestBuffer = MaxLenRingBuffer<u16, 3>;
+
+    #[test]
+    fn push_and_iterate() {
+        let mut buffer = TestBuffer::new();
+        assert!(buffer.latest().is_none());
+        assert!(buffer.oldest().is_none());
+        assert_eq!(buffer.push(1), None);
+        assert_eq!(buffer.push(2), None);
+        assert_eq!(buffer.latest(), Some(&2));
+        assert_eq!(buffer.oldest(), Some(&1));
+        assert_eq!(buffer.push(3), None);
+        assert!(buffer.is_full());
+        assert_eq!(buffer.push(4), Some(1));
+        assert_eq!(buffer.push(5), Some(2));
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(
+            buffer.iter().rev().copied().collect::<Vec<_>>(),
+            vec![5, 4, 3]
+        );
+        assert_eq!(buffer.latest(), Some(&5));
+        assert_eq!(buffer.oldest(), Some(&3));
+        assert_eq!(buffer.get(1), Some(&4));
+        assert_eq!(buffer.get(3), None);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(Vec::from(buffer.clone()), vec![3, 4, 5]);
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.push(6), None);
+        assert_eq!(buffer.oldest(), buffer.latest());
+    }
+
+    #[test]
+    fn borsh_layout() {
+        let mut buffer = TestBuffer::new();
+        for i in 1..=4 {
+            buffer.push(i);
+        }
+        let bytes = buffer.try_to_vec().unwrap();
+        assert_eq!(bytes, vec![1, 0, 0, 0, 3, 0, 0, 0, 4, 0, 2, 0, 3, 0]);
+        assert_eq!(bytes.len(), TestBuffer::MAX_SERIALIZED_LEN);
+        assert_eq!(TestBuffer::try_from_slice(&bytes).unwrap(), buffer);
+
+        let empty = TestBuffer::new().try_to_vec().unwrap();
+        assert_eq!(empty.len(), TestBuffer::MIN_SERIALIZED_LEN);
+
+        // head out of bounds
+        assert!(TestBuffer::try_from_slice(&[3, 0, 0, 0, 3, 0, 0, 0, 4, 0, 2, 0, 3, 0]).is_err());
+        // moved head while not full
+        assert!(TestBuffer::try_from_slice(&[1, 0, 0, 0, 2, 0, 0, 0, 4, 0, 2, 0]).is_err());
+        // too many elements
+        assert!(
+            TestBuffer::try_from_slice(&[0, 0, 0, 0, 4, 0, 0, 0, 1, 0, 2, 0, 3, 0, 4, 0]).is_err()
+        );
+        // huge length prefix without the data
+        assert!(TestBuffer::try_from_slice(&[0, 0, 0, 0, 255, 255, 255, 255]).is_err());
+    }
+}
@@ -1,11 +1,12 @@
-use super::{MaxSerializedLen, CONTENTS_FULL};
+use super::{deserialize_max_len, MaxSerializedLen, MinSerializedLen, CONTENTS_FULL};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use std::convert::{From, TryFrom};
+use std::io::{Error, ErrorKind};
 
 #[repr(C)]
-#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[derive(BorshSerialize, Clone, Debug)]
 pub struct MaxLenString<const N: usize> {
     contents: String,
 }
@@ -14,6 +15,10 @@ impl<const N: usize> MaxSerializedLen for MaxLenString<N> {
     const MAX_SERIALIZED_LEN: usize = 4 + N;
 }
 
+impl<const N: usize> MinSerializedLen for MaxLenString<N> {
+    const MIN_SERIALIZED_LEN: usize = 4;
+}
+
 impl<const N: usize> MaxLenString<N> {
     pub fn contents(&self) -> &str {
         self.contents.as_str()
@@ -28,7 +33,7 @@ impl<const N: usize> TryFrom<String> for MaxLenString<N> {
     type Error = &'static str;
 
     fn try_from(string: String) -> Result<Self, Self::Error> {
-        if string.as_bytes().len() > N {
+        if string.len() > N {
             return Err(CONTENTS_FULL);
         }
         Ok(Self { contents: string })
@@ -49,6 +54,23 @@ impl<const N: usize> From<MaxLenString<N>> for String {
     }
 }
 
+impl<const N: usize> BorshDeserialize for MaxLenString<N> {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let len = deserialize_max_len::<N>(buf)?;
+        if buf.len() < len {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "unexpected end of string",
+            ));
+        }
+        let (bytes, rest) = buf.split_at(len);
+        let contents = String::from_utf8(bytes.to_vec())
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        *buf = rest;
+        Ok(Self { contents })
+    }
+}
+
 #[cfg(test)]
 mod test_max_len_string {
     use super::*;
@@ -88,4 +110,26 @@ mod test_max_len_string {
             TestString::MAX_SERIALIZED_LEN
         );
     }
+
+    #[test]
+    fn deserialization() {
+        let test_string = TestString::try_from("asdef").unwrap();
+        let bytes = test_string.try_to_vec().unwrap();
+        assert_eq!(
+            TestString::try_from_slice(&bytes).unwrap().contents(),
+            "asdef"
+        );
+
+        // 6 bytes
+        assert!(TestString::try_from_slice(&[6, 0, 0, 0, 97, 115, 100, 101, 102, 103]).is_err());
+        // huge length prefix without the data
+        assert!(TestString::try_from_slice(&[255, 255, 255, 255]).is_err());
+        // truncated
+        assert!(TestString::try_from_slice(&[3, 0, 0, 0, 97, 115]).is_err());
+        // invalid utf-8
+        assert!(TestString::try_from_slice(&[2, 0, 0, 0, 0xc3, 0x28]).is_err());
+        // multi-byte characters count by their byte length
+        assert!(TestString::try_from_slice(&"ééé".to_string().try_to_vec().unwrap()).is_err());
+        assert!(TestString::try_from_slice(&"éé".to_string().try_to_vec().unwrap()).is_ok());
+    }
 }
@@ -1,11 +1,13 @@
-use super::{MaxLenResult, MaxSerializedLen, CONTENTS_FULL};
+use super::{deserialize_max_len, MaxLenResult, MaxSerializedLen, MinSerializedLen, CONTENTS_FULL};
 use borsh::{BorshDeserialize, BorshSerialize};
 use std::convert::{From, TryFrom};
+use std::ops::{Deref, DerefMut, Index, IndexMut, RangeBounds};
+use std::slice::SliceIndex;
 
 // NOTE anyhow doesn't compile under bpf it seems
 
 #[repr(C)]
-#[derive(BorshDeserialize, BorshSerialize, Clone, Debug)]
+#[derive(BorshSerialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MaxLenVec<T, const N: usize> {
     contents: Vec<T>,
 }
@@ -17,6 +19,10 @@ where
     const MAX_SERIALIZED_LEN: usize = 4 + N * T::MAX_SERIALIZED_LEN;
 }
 
+impl<T, const N: usize> MinSerializedLen for MaxLenVec<T, N> {
+    const MIN_SERIALIZED_LEN: usize = 4;
+}
+
 impl<T, const N: usize> MaxLenVec<T, N> {
     pub fn new() -> Self {
         MaxLenVec {
@@ -24,6 +30,16 @@ impl<T, const N: usize> MaxLenVec<T, N> {
         }
     }
 
+    /// Maximum number of elements the vector can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Number of elements that can still be pushed into the vector.
+    pub fn remaining_capacity(&self) -> usize {
+        N - self.contents.len()
+    }
+
     pub fn is_full(&self) -> bool {
         self.contents.len() == N
     }
@@ -73,8 +89,74 @@ impl<T, const N: usize> MaxLenVec<T, N> {
         }
     }
 
-    pub fn remove(&mut self, index: usize) {
-        self.contents.remove(index);
+    /// Removes and returns the element at `index`, shifting all elements
+    /// after it to the left.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.contents.remove(index)
+    }
+
+    /// Removes and returns the element at `index`, replacing it with the last
+    /// element.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        self.contents.swap_remove(index)
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.contents.truncate(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.contents.clear();
+    }
+
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.contents.retain(f);
+    }
+
+    pub fn drain<R>(&mut self, range: R) -> std::vec::Drain<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.contents.drain(range)
+    }
+
+    /// Appends every element of the iterator. Fails without modifying the
+    /// vector if they don't fit.
+    pub fn try_extend<I>(&mut self, iter: I) -> MaxLenResult
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let len = self.contents.len();
+        for elem in iter {
+            if self.is_full() {
+                self.contents.truncate(len);
+                return Err(CONTENTS_FULL);
+            }
+            self.contents.push(elem);
+        }
+        Ok(())
+    }
+
+    /// Resizes the vector to `new_len`, filling new slots with `value`.
+    pub fn try_resize(&mut self, new_len: usize, value: T) -> MaxLenResult
+    where
+        T: Clone,
+    {
+        if new_len > N {
+            Err(CONTENTS_FULL)
+        } else {
+            self.contents.resize(new_len, value);
+            Ok(())
+        }
     }
 
     pub fn get_last_element(&self) -> Option<&T> {
@@ -84,6 +166,14 @@ impl<T, const N: usize> MaxLenVec<T, N> {
             Some(&self.contents[self.contents.len() - 1])
         }
     }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.contents.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.contents.iter_mut()
+    }
 }
 
 impl<T, const N: usize> TryFrom<Vec<T>> for MaxLenVec<T, N> {
@@ -98,6 +188,14 @@ impl<T, const N: usize> TryFrom<Vec<T>> for MaxLenVec<T, N> {
     }
 }
 
+impl<T: Clone, const N: usize> TryFrom<&[T]> for MaxLenVec<T, N> {
+    type Error = &'static str;
+
+    fn try_from(slice: &[T]) -> Result<Self, Self::Error> {
+        Self::try_from(slice.to_vec())
+    }
+}
+
 impl<T, const N: usize> Default for MaxLenVec<T, N> {
     fn default() -> Self {
         Self::new()
@@ -110,6 +208,108 @@ impl<T, const N: usize> From<MaxLenVec<T, N>> for Vec<T> {
     }
 }
 
+impl<T, const N: usize> Deref for MaxLenVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.contents.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for MaxLenVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.contents.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for MaxLenVec<T, N> {
+    fn as_ref(&self) -> &[T] {
+        self.contents.as_slice()
+    }
+}
+
+impl<T, I, const N: usize> Index<I> for MaxLenVec<T, N>
+where
+    I: SliceIndex<[T]>,
+{
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &self.contents[index]
+    }
+}
+
+impl<T, I, const N: usize> IndexMut<I> for MaxLenVec<T, N>
+where
+    I: SliceIndex<[T]>,
+{
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        &mut self.contents[index]
+    }
+}
+
+impl<T, const N: usize> IntoIterator for MaxLenVec<T, N> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contents.into_iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a MaxLenVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contents.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut MaxLenVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contents.iter_mut()
+    }
+}
+
+impl<T, const N: usize> BorshDeserialize for MaxLenVec<T, N>
+where
+    T: BorshDeserialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let len = deserialize_max_len::<N>(buf)?;
+        let mut contents = Vec::with_capacity(len);
+        for _ in 0..len {
+            contents.push(T::deserialize(buf)?);
+        }
+        Ok(Self { contents })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, const N: usize> serde::Serialize for MaxLenVec<T, N>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.contents.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, const N: usize> serde::Deserialize<'de> for MaxLenVec<T, N>
+where
+    T: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let contents = Vec::<T>::deserialize(deserializer)?;
+        Self::try_from(contents).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod test_max_len_vec {
     use super::*;
@@ -192,4 +392,75 @@ mod test_max_len_vec {
             TestVec::MAX_SERIALIZED_LEN
         );
     }
+
+    #[test]
+    fn std_api() {
+        let mut vec = TestVec::try_from([1, 2, 3].as_slice()).unwrap();
+        assert_eq!(vec.capacity(), CAPACITY);
+        assert_eq!(vec.remaining_capacity(), 2);
+        assert_eq!(vec[1], 2);
+        assert_eq!(&vec[1..], &[2, 3]);
+        vec[0] = 7;
+        assert_eq!(vec.first(), Some(&7));
+        assert_eq!(vec.iter().sum::<u8>(), 12);
+        for elem in &mut vec {
+            *elem += 1;
+        }
+        assert_eq!(
+            (&vec).into_iter().copied().collect::<Vec<_>>(),
+            vec![8, 3, 4]
+        );
+        assert_eq!(vec, TestVec::try_from(vec![8, 3, 4]).unwrap());
+
+        // failed extensions leave the vector untouched
+        assert_eq!(vec.try_extend(vec![5, 6, 7]), Err(CONTENTS_FULL));
+        assert_eq!(vec.contents(), &[8, 3, 4]);
+        assert!(vec.try_extend(vec![5, 6]).is_ok());
+        assert!(vec.is_full());
+        assert_eq!(vec.try_resize(6, 0), Err(CONTENTS_FULL));
+        assert!(vec.try_resize(2, 0).is_ok());
+        assert_eq!(vec.contents(), &[8, 3]);
+        assert!(vec.try_extend(vec![1, 9, 2]).is_ok());
+        assert_eq!(vec.contents(), &[8, 3, 1, 9, 2]);
+
+        assert_eq!(vec.remove(1), 3);
+        assert_eq!(vec.swap_remove(0), 8);
+        assert_eq!(vec.contents(), &[2, 1, 9]);
+        vec.retain(|elem| *elem != 1);
+        assert_eq!(vec.contents(), &[2, 9]);
+        assert!(vec.try_extend(vec![4, 5]).is_ok());
+        assert_eq!(vec.drain(1..3).collect::<Vec<_>>(), vec![9, 4]);
+        assert_eq!(vec.contents(), &[2, 5]);
+        vec.truncate(1);
+        assert_eq!(vec.into_iter().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let vec = TestVec::try_from(vec![1, 2, 3]).unwrap();
+        let json = serde_json::to_string(&vec).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        assert_eq!(serde_json::from_str::<TestVec>(&json).unwrap(), vec);
+        assert!(serde_json::from_str::<TestVec>("[1,2,3,4,5,6]").is_err());
+    }
+
+    #[test]
+    fn deserialization() {
+        let vec = TestVec::try_from(vec![1, 2, 3]).unwrap();
+        let bytes = vec.try_to_vec().unwrap();
+        assert_eq!(bytes, vec![3, 0, 0, 0, 1, 2, 3]);
+        assert_eq!(TestVec::try_from_slice(&bytes).unwrap(), vec);
+
+        // 6 elements
+        assert!(TestVec::try_from_slice(&[6, 0, 0, 0, 1, 2, 3, 4, 5, 6]).is_err());
+        // huge length prefix without the data
+        assert!(TestVec::try_from_slice(&[255, 255, 255, 255]).is_err());
+        // truncated
+        assert!(TestVec::try_from_slice(&[3, 0, 0, 0, 1, 2]).is_err());
+        // nested vectors are checked as well
+        type NestedVec = MaxLenVec<MaxLenVec<u16, 1>, 2>;
+        assert!(NestedVec::try_from_slice(&[1, 0, 0, 0, 1, 0, 0, 0, 7, 0]).is_ok());
+        assert!(NestedVec::try_from_slice(&[1, 0, 0, 0, 2, 0, 0, 0, 7, 0, 8, 0]).is_err());
+    }
 }
@@ -47,11 +47,11 @@ use std::marker::PhantomData;
 /// # }
 /// ```
 ///
-/// # Notes
-/// Note, that for enums with more than ~15 variants, the compiler hangs due to
-/// the way the maximum lengths of the variants are computed. Therefore, it is
-/// not recommended to derive `MaxSerializedLen` for enums like that, rather it
-/// should be implemented manually.
+/// # Generics
+/// The derive adds a `T: MaxSerializedLen` bound for every type parameter
+/// used in fields without a `len` attribute (except within `PhantomData`).
+/// The inferred bounds can be replaced via the `max_len` attribute, e.g.
+/// `#[max_len(bound = "T: MaxSerializedLen + Copy")]`.
 pub trait MaxSerializedLen {
     const MAX_SERIALIZED_LEN: usize;
 }
@@ -75,8 +75,48 @@ impl_max_serialized_length!(i16, 2);
 impl_max_serialized_length!(i32, 4);
 impl_max_serialized_length!(i64, 8);
 impl_max_serialized_length!(i128, 16);
+impl_max_serialized_length!(f32, 4);
+impl_max_serialized_length!(f64, 8);
 impl_max_serialized_length!(Pubkey, 32);
-impl_max_serialized_length!([u8; 32], 32);
+impl_max_serialized_length!((), 0);
+
+macro_rules! impl_max_serialized_length_tuple {
+    ($($name:ident),+) => {
+        impl<$($name),+> MaxSerializedLen for ($($name,)+)
+        where
+            $($name: MaxSerializedLen),+
+        {
+            const MAX_SERIALIZED_LEN: usize = 0 $(+ $name::MAX_SERIALIZED_LEN)+;
+        }
+    };
+}
+
+impl_max_serialized_length_tuple!(T0);
+impl_max_serialized_length_tuple!(T0, T1);
+impl_max_serialized_length_tuple!(T0, T1, T2);
+impl_max_serialized_length_tuple!(T0, T1, T2, T3);
+impl_max_serialized_length_tuple!(T0, T1, T2, T3, T4);
+impl_max_serialized_length_tuple!(T0, T1, T2, T3, T4, T5);
+impl_max_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6);
+impl_max_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7);
+impl_max_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8);
+impl_max_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_max_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_max_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+
+impl<T, const N: usize> MaxSerializedLen for [T; N]
+where
+    T: MaxSerializedLen,
+{
+    const MAX_SERIALIZED_LEN: usize = N * T::MAX_SERIALIZED_LEN;
+}
+
+impl<T> MaxSerializedLen for Box<T>
+where
+    T: MaxSerializedLen,
+{
+    const MAX_SERIALIZED_LEN: usize = T::MAX_SERIALIZED_LEN;
+}
 
 impl<T> MaxSerializedLen for Option<T>
 where
@@ -253,4 +293,219 @@ mod test {
         );
         assert_eq!(GhastlyStruct::<DummyUnitEnum>::MAX_SERIALIZED_LEN, 34);
     }
+
+    #[derive(MaxSerializedLen, BorshSerialize, BorshDeserialize, Debug)]
+    enum LargeEnum {
+        V0,
+        V1(u64),
+        V2 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V3,
+        V4(u64),
+        V5 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V6,
+        V7(u64),
+        V8 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V9,
+        V10(u64),
+        V11 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V12,
+        V13(u64),
+        V14 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V15,
+        V16(u64),
+        V17 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V18,
+        V19(u64),
+        V20 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V21,
+        V22(u64),
+        V23 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V24,
+        V25(u64),
+        V26 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V27,
+        V28(u64),
+        V29 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V30,
+        V31(u64),
+        V32 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V33,
+        V34(u64),
+        V35 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V36,
+        V37(u64),
+        V38 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V39,
+        V40(u64),
+        V41(u128, Pubkey),
+        V42,
+        V43(u64),
+        V44 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V45,
+        V46(u64),
+        V47 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V48,
+        V49(u64),
+        V50 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V51,
+        V52(u64),
+        V53 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V54,
+        V55(u64),
+        V56 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V57,
+        V58(u64),
+        V59 {
+            a: u32,
+            b: Option<u8>,
+        },
+        V60,
+        V61(u64),
+        V62 {
+            a: u32,
+            b: Option<u8>,
+        },
+        #[len(40)]
+        V63(String),
+    }
+
+    #[test]
+    fn large_enum() {
+        assert_eq!(LargeEnum::MAX_SERIALIZED_LEN, 1 + 48);
+        let en = LargeEnum::V41(u128::MAX, Pubkey::new_unique());
+        assert_eq!(
+            en.try_to_vec().unwrap().len(),
+            LargeEnum::MAX_SERIALIZED_LEN
+        );
+        let en = LargeEnum::V62 { a: 1, b: Some(2) };
+        assert_eq!(en.try_to_vec().unwrap().len(), 7);
+    }
+
+    #[derive(MaxSerializedLen, BorshSerialize, BorshDeserialize, Debug)]
+    struct CompoundTypes {
+        tuple: (u8, Pubkey, Option<u16>),
+        nested_array: [[u16; 2]; 3],
+        boxed: Box<Option<u32>>,
+        unit: (),
+        qualified: std::marker::PhantomData<u8>,
+        generic: Option<(i8, [Option<u8>; 4])>,
+    }
+
+    #[derive(MaxSerializedLen, BorshSerialize, Debug)]
+    struct Borrowed<'a> {
+        foo: &'a u64,
+        bar: &'a [u8; 3],
+    }
+
+    #[test]
+    fn compound_types() {
+        let compound = CompoundTypes {
+            tuple: (1, Pubkey::new_unique(), Some(3)),
+            nested_array: [[1, 2], [3, 4], [5, 6]],
+            boxed: Box::new(Some(7)),
+            unit: (),
+            qualified: PhantomData,
+            generic: Some((-1, [Some(2); 4])),
+        };
+        assert_eq!(CompoundTypes::MAX_SERIALIZED_LEN, 36 + 12 + 5 + 10);
+        assert_eq!(
+            compound.try_to_vec().unwrap().len(),
+            CompoundTypes::MAX_SERIALIZED_LEN
+        );
+
+        let borrowed = Borrowed {
+            foo: &5,
+            bar: &[1, 2, 3],
+        };
+        assert_eq!(
+            borrowed.try_to_vec().unwrap().len(),
+            Borrowed::MAX_SERIALIZED_LEN
+        );
+        assert_eq!(<(u8, u16)>::MAX_SERIALIZED_LEN, 3);
+        assert_eq!(<[Pubkey; 3]>::MAX_SERIALIZED_LEN, 96);
+        assert_eq!(<()>::MAX_SERIALIZED_LEN, 0);
+    }
+
+    #[derive(MaxSerializedLen, BorshSerialize, BorshDeserialize, Debug)]
+    struct Pool<T> {
+        items: crate::MaxLenVec<T, 8>,
+        best: Option<(T, u8)>,
+        marker: PhantomData<T>,
+    }
+
+    #[derive(MaxSerializedLen, BorshSerialize, BorshDeserialize, Debug)]
+    enum Either<L, R> {
+        Left(L),
+        #[len(16)]
+        Right(R),
+    }
+
+    #[derive(MaxSerializedLen, BorshSerialize, BorshDeserialize, Debug)]
+    #[max_len(bound = "T: MaxSerializedLen + Copy")]
+    struct Bounded<T> {
+        inner: [T; 2],
+    }
+
+    #[test]
+    fn inferred_bounds() {
+        assert_eq!(Pool::<u16>::MAX_SERIALIZED_LEN, 4 + 8 * 2 + 1 + 3);
+        // `String` doesn't implement `MaxSerializedLen`, but it's not needed
+        // due to the `len` attribute
+        assert_eq!(Either::<u64, String>::MAX_SERIALIZED_LEN, 17);
+        assert_eq!(Bounded::<u32>::MAX_SERIALIZED_LEN, 8);
+    }
 }
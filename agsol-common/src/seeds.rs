@@ -0,0 +1,171 @@
+/// Declares a named PDA seed recipe once, so that the program and its
+/// off-chain clients derive addresses the same way.
+///
+/// The macro generates a module with
+/// - `find_address(program_id, ...) -> (Pubkey, u8)` that finds the PDA and
+///   its bump, available everywhere including wasm targets
+/// - `verify(program_id, ..., account) -> Result<OwnedSignerPda, ProgramError>`
+///   that checks whether the account is the PDA and returns its signer seeds
+/// - `verify_with_bump(program_id, ..., bump, account)` that does the same
+///   with a known bump, avoiding the costly bump search
+///
+/// The verifiers are not compiled for `wasm32` targets. Seeds may be any
+/// expression of the arguments that can be referenced as a byte slice.
+///
+/// The verifiers return an [`OwnedSignerPda`](crate::OwnedSignerPda) instead
+/// of a [`SignerPda`](crate::SignerPda), because the latter borrows its seeds
+/// and the seeds computed from the arguments don't outlive the call.
+///
+/// # Examples
+/// ```rust
+/// use solana_program::pubkey::Pubkey;
+///
+/// agsol_common::seeds! {
+///     /// Seeds of the vault holding the deposits of a user.
+///     pub mod vault_seeds(authority: &Pubkey, id: u64) = [
+///         b"vault",
+///         authority,
+///         &id.to_le_bytes(),
+///     ];
+/// }
+///
+/// # fn main() {
+/// let program_id = Pubkey::new_unique();
+/// let authority = Pubkey::new_unique();
+/// let (vault, bump) = vault_seeds::find_address(&program_id, &authority, 1);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! seeds {
+    (
+        $(#[$meta:meta])*
+        $vis:vis mod $name:ident ($($arg:ident : $ty:ty),* $(,)?) = [$($seed:expr),* $(,)?];
+    ) => {
+        $(#[$meta])*
+        #[allow(dead_code)]
+        $vis mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// Finds the PDA and its bump.
+            pub fn find_address(
+                program_id: &::solana_program::pubkey::Pubkey,
+                $($arg: $ty),*
+            ) -> (::solana_program::pubkey::Pubkey, u8) {
+                ::solana_program::pubkey::Pubkey::find_program_address(
+                    &[$(::core::convert::AsRef::<[u8]>::as_ref($seed)),*],
+                    program_id,
+                )
+            }
+
+            /// Checks whether the account is the PDA.
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn verify(
+                program_id: &::solana_program::pubkey::Pubkey,
+                $($arg: $ty,)*
+                account: &::solana_program::account_info::AccountInfo,
+            ) -> Result<$crate::OwnedSignerPda, ::solana_program::program_error::ProgramError> {
+                $crate::OwnedSignerPda::new_checked(
+                    &[$(::core::convert::AsRef::<[u8]>::as_ref($seed)),*],
+                    program_id,
+                    account,
+                )
+            }
+
+            /// Checks whether the account is the PDA with the known bump.
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn verify_with_bump(
+                program_id: &::solana_program::pubkey::Pubkey,
+                $($arg: $ty,)*
+                bump: u8,
+                account: &::solana_program::account_info::AccountInfo,
+            ) -> Result<$crate::OwnedSignerPda, ::solana_program::program_error::ProgramError> {
+                $crate::OwnedSignerPda::new_with_bump(
+                    &[$(::core::convert::AsRef::<[u8]>::as_ref($seed)),*],
+                    bump,
+                    program_id,
+                    account,
+                )
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use solana_program::pubkey::Pubkey;
+
+    seeds! {
+        mod vault_seeds(authority: &Pubkey, id: u64) = [b"vault", authority, &id.to_le_bytes()];
+    }
+
+    seeds! {
+        pub(crate) mod config_seeds() = [b"config"];
+    }
+
+    // also checked for wasm targets in CI, where the verifiers are missing
+    #[test]
+    fn find_addresses() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        assert_eq!(
+            vault_seeds::find_address(&program_id, &authority, 7),
+            Pubkey::find_program_address(
+                &[b"vault", authority.as_ref(), &7_u64.to_le_bytes()],
+                &program_id
+            )
+        );
+        assert_ne!(
+            config_seeds::find_address(&program_id),
+            vault_seeds::find_address(&program_id, &authority, 7)
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn verify_accounts() {
+        use solana_program::account_info::AccountInfo;
+        use solana_program::program_error::ProgramError;
+
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let (vault, bump) = vault_seeds::find_address(&program_id, &authority, 7);
+
+        let mut lamports = 0;
+        let mut data = [];
+        let account = AccountInfo::new(
+            &vault,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+        let signer_pda = vault_seeds::verify(&program_id, &authority, 7, &account).unwrap();
+        assert_eq!(signer_pda.pda, vault);
+        assert_eq!(signer_pda.bump, [bump]);
+        assert_eq!(
+            signer_pda.signer_seeds(),
+            vec![
+                b"vault".as_slice(),
+                authority.as_ref(),
+                &7_u64.to_le_bytes(),
+                &[bump]
+            ]
+        );
+        assert_eq!(
+            vault_seeds::verify_with_bump(&program_id, &authority, 7, bump, &account),
+            Ok(signer_pda)
+        );
+        assert_eq!(
+            vault_seeds::verify(&program_id, &authority, 8, &account),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            config_seeds::verify(&program_id, &account),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+}
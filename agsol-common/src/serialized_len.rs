@@ -0,0 +1,302 @@
+use solana_program::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::marker::PhantomData;
+
+/// Trait that provides the exact length of the serialized byte stream of a
+/// borsh-serializable data structure with a fixed layout.
+///
+/// Deriving it for a data structure that contains variable-size fields (e.g.
+/// `Vec`, `String` or `Option`) results in a compile error, therefore it is
+/// guaranteed that every instance serializes into exactly `SERIALIZED_LEN`
+/// bytes. Enums are only fixed-size if all of their variants have the same
+/// serialized length, otherwise the derive fails to compile (generic enums
+/// fail once their `SERIALIZED_LEN` is used).
+///
+/// # Examples
+/// ```rust
+/// # #[macro_use]
+/// # extern crate agsol_common_derive;
+/// use agsol_common::SerializedLen;
+/// use borsh::{BorshSerialize, BorshDeserialize};
+/// use solana_program::pubkey::Pubkey;
+///
+/// #[derive(BorshSerialize, BorshDeserialize, SerializedLen)]
+/// struct Position {
+///     owner: Pubkey,          // len: 32
+///     amounts: [u64; 4],      // len: 32
+///     bump: (u8, [u16; 2]),   // len: 5
+/// }
+///
+/// #[derive(BorshSerialize, BorshDeserialize, SerializedLen)]
+/// enum Side {
+///     Bid(u64), // len: 1 + 8
+///     Ask(i64), // len: 1 + 8
+/// }
+///
+/// # fn main() {
+/// assert_eq!(Position::SERIALIZED_LEN, 69);
+/// assert_eq!(Side::SERIALIZED_LEN, 9);
+/// # }
+/// ```
+///
+/// ```compile_fail
+/// # #[macro_use]
+/// # extern crate agsol_common_derive;
+/// use agsol_common::SerializedLen;
+/// use borsh::{BorshSerialize, BorshDeserialize};
+///
+/// #[derive(BorshSerialize, BorshDeserialize, SerializedLen)]
+/// enum Side {
+///     Bid(u64), // len: 1 + 8
+///     Ask(u32), // len: 1 + 4
+/// }
+/// # fn main() {}
+/// ```
+pub trait SerializedLen {
+    const SERIALIZED_LEN: usize;
+}
+
+/// Trait that provides the minimum length of the serialized byte stream of a
+/// borsh-serializable data structure.
+///
+/// Useful for rejecting truncated account data before decoding it.
+///
+/// # Examples
+/// ```rust
+/// # #[macro_use]
+/// # extern crate agsol_common_derive;
+/// use agsol_common::MinSerializedLen;
+/// use borsh::{BorshSerialize, BorshDeserialize};
+///
+/// #[derive(BorshSerialize, BorshDeserialize, MinSerializedLen)]
+/// struct Profile {
+///     id: u32,               // min len: 4
+///     name: String,          // min len: 4
+///     avatar: Option<[u8; 32]>, // min len: 1
+/// }
+///
+/// #[derive(BorshSerialize, BorshDeserialize, MinSerializedLen)]
+/// enum Action {
+///     Create(Profile), // min len: 1 + 9
+///     Delete,          // min len: 1
+/// }
+///
+/// # fn main() {
+/// assert_eq!(Profile::MIN_SERIALIZED_LEN, 9);
+/// assert_eq!(Action::MIN_SERIALIZED_LEN, 1);
+/// # }
+/// ```
+pub trait MinSerializedLen {
+    const MIN_SERIALIZED_LEN: usize;
+}
+
+macro_rules! impl_serialized_length {
+    ($this:ty, $len:expr) => {
+        impl SerializedLen for $this {
+            const SERIALIZED_LEN: usize = $len;
+        }
+
+        impl MinSerializedLen for $this {
+            const MIN_SERIALIZED_LEN: usize = $len;
+        }
+    };
+}
+
+impl_serialized_length!(bool, 1);
+impl_serialized_length!(u8, 1);
+impl_serialized_length!(u16, 2);
+impl_serialized_length!(u32, 4);
+impl_serialized_length!(u64, 8);
+impl_serialized_length!(u128, 16);
+impl_serialized_length!(i8, 1);
+impl_serialized_length!(i16, 2);
+impl_serialized_length!(i32, 4);
+impl_serialized_length!(i64, 8);
+impl_serialized_length!(i128, 16);
+impl_serialized_length!(f32, 4);
+impl_serialized_length!(f64, 8);
+impl_serialized_length!(Pubkey, 32);
+impl_serialized_length!((), 0);
+
+macro_rules! impl_serialized_length_tuple {
+    ($($name:ident),+) => {
+        impl<$($name),+> SerializedLen for ($($name,)+)
+        where
+            $($name: SerializedLen),+
+        {
+            const SERIALIZED_LEN: usize = 0 $(+ $name::SERIALIZED_LEN)+;
+        }
+
+        impl<$($name),+> MinSerializedLen for ($($name,)+)
+        where
+            $($name: MinSerializedLen),+
+        {
+            const MIN_SERIALIZED_LEN: usize = 0 $(+ $name::MIN_SERIALIZED_LEN)+;
+        }
+    };
+}
+
+impl_serialized_length_tuple!(T0);
+impl_serialized_length_tuple!(T0, T1);
+impl_serialized_length_tuple!(T0, T1, T2);
+impl_serialized_length_tuple!(T0, T1, T2, T3);
+impl_serialized_length_tuple!(T0, T1, T2, T3, T4);
+impl_serialized_length_tuple!(T0, T1, T2, T3, T4, T5);
+impl_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6);
+impl_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7);
+impl_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8);
+impl_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_serialized_length_tuple!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+
+impl<T, const N: usize> SerializedLen for [T; N]
+where
+    T: SerializedLen,
+{
+    const SERIALIZED_LEN: usize = N * T::SERIALIZED_LEN;
+}
+
+impl<T, const N: usize> MinSerializedLen for [T; N]
+where
+    T: MinSerializedLen,
+{
+    const MIN_SERIALIZED_LEN: usize = N * T::MIN_SERIALIZED_LEN;
+}
+
+impl<T> SerializedLen for Box<T>
+where
+    T: SerializedLen,
+{
+    const SERIALIZED_LEN: usize = T::SERIALIZED_LEN;
+}
+
+impl<T> MinSerializedLen for Box<T>
+where
+    T: MinSerializedLen,
+{
+    const MIN_SERIALIZED_LEN: usize = T::MIN_SERIALIZED_LEN;
+}
+
+impl<T> SerializedLen for PhantomData<T> {
+    const SERIALIZED_LEN: usize = 0;
+}
+
+impl<T> MinSerializedLen for PhantomData<T> {
+    const MIN_SERIALIZED_LEN: usize = 0;
+}
+
+impl<T> MinSerializedLen for Option<T> {
+    const MIN_SERIALIZED_LEN: usize = 1;
+}
+
+macro_rules! impl_min_serialized_length_collection {
+    ($($this:ident<$($param:ident),+>),+) => {
+        $(impl<$($param),+> MinSerializedLen for $this<$($param),+> {
+            const MIN_SERIALIZED_LEN: usize = 4;
+        })+
+    };
+}
+
+impl_min_serialized_length_collection!(
+    Vec<T>,
+    VecDeque<T>,
+    BTreeSet<T>,
+    HashSet<T>,
+    BTreeMap<K, V>,
+    HashMap<K, V>
+);
+
+impl MinSerializedLen for String {
+    const MIN_SERIALIZED_LEN: usize = 4;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MaxLenString, MaxLenVec};
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[derive(BorshSerialize, BorshDeserialize, SerializedLen, MinSerializedLen, Debug)]
+    struct Fixed {
+        a: u64,
+        b: [Pubkey; 2],
+        c: (u8, i16),
+        d: Box<[u32; 3]>,
+        e: PhantomData<String>,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, SerializedLen, MinSerializedLen, Debug)]
+    enum FixedEnum {
+        A(u32),
+        B { a: u16, b: [u8; 2] },
+        C((i8, [u8; 3])),
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, SerializedLen, Debug)]
+    struct Generic<T> {
+        items: [T; 4],
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, MinSerializedLen, Debug)]
+    struct Variable {
+        a: u8,
+        b: Vec<u64>,
+        c: Option<Pubkey>,
+        d: MaxLenString<32>,
+        e: MaxLenVec<u16, 8>,
+        f: String,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, MinSerializedLen, Debug)]
+    enum VariableEnum {
+        Empty,
+        Full(Variable),
+    }
+
+    #[test]
+    fn exact_serialized_len() {
+        let fixed = Fixed {
+            a: 5,
+            b: [Pubkey::new_unique(), Pubkey::new_unique()],
+            c: (1, -1),
+            d: Box::new([1, 2, 3]),
+            e: PhantomData,
+        };
+        assert_eq!(Fixed::SERIALIZED_LEN, 8 + 64 + 3 + 12);
+        assert_eq!(fixed.try_to_vec().unwrap().len(), Fixed::SERIALIZED_LEN);
+        assert_eq!(Fixed::MIN_SERIALIZED_LEN, Fixed::SERIALIZED_LEN);
+
+        assert_eq!(FixedEnum::SERIALIZED_LEN, 5);
+        let fixed_enum = FixedEnum::B { a: 1, b: [2, 3] };
+        assert_eq!(
+            fixed_enum.try_to_vec().unwrap().len(),
+            FixedEnum::SERIALIZED_LEN
+        );
+        assert_eq!(FixedEnum::MIN_SERIALIZED_LEN, 5);
+
+        assert_eq!(Generic::<u16>::SERIALIZED_LEN, 8);
+        assert_eq!(<(u8, [u64; 2])>::SERIALIZED_LEN, 17);
+    }
+
+    #[test]
+    fn min_serialized_len() {
+        let variable = Variable {
+            a: 0,
+            b: Vec::new(),
+            c: None,
+            d: MaxLenString::try_from("").unwrap(),
+            e: MaxLenVec::new(),
+            f: String::new(),
+        };
+        assert_eq!(Variable::MIN_SERIALIZED_LEN, 1 + 4 + 1 + 4 + 4 + 4);
+        assert_eq!(
+            variable.try_to_vec().unwrap().len(),
+            Variable::MIN_SERIALIZED_LEN
+        );
+        assert_eq!(VariableEnum::MIN_SERIALIZED_LEN, 1);
+        assert_eq!(
+            VariableEnum::Empty.try_to_vec().unwrap().len(),
+            VariableEnum::MIN_SERIALIZED_LEN
+        );
+    }
+}
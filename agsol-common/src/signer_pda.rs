@@ -42,6 +42,47 @@ impl<'a, 'b> SignerPda<'a, 'b> {
         }
     }
 
+    /// Same as [`new_checked`](Self::new_checked) but verifies a known bump
+    /// (e.g. one stored in the account state) instead of searching for it,
+    /// which costs a lot less compute units.
+    ///
+    /// Any bump deriving the expected address is accepted, so it must be the
+    /// canonical bump found by [`new_checked`](Self::new_checked) when the
+    /// account was created and stored afterwards, never one supplied by the
+    /// caller of the instruction. Otherwise several addresses could pass as
+    /// the same PDA.
+    pub fn new_with_bump(
+        seeds: &'b [&'a [u8]],
+        bump: u8,
+        program_id: &Pubkey,
+        expected: &AccountInfo,
+    ) -> Result<Self, ProgramError> {
+        let pda = create_and_check(seeds, bump, program_id, expected.key)?;
+        Ok(Self {
+            pda,
+            bump: [bump],
+            seeds,
+        })
+    }
+
+    /// Same as [`check_owner`](Self::check_owner) but verifies a known bump
+    /// instead of searching for it. The bump must be the stored canonical
+    /// one, see [`new_with_bump`](Self::new_with_bump).
+    pub fn check_owner_with_bump(
+        seeds: &'b [&'a [u8]],
+        bump: u8,
+        program_id: &Pubkey,
+        owner: &Pubkey,
+        expected: &AccountInfo,
+    ) -> Result<(), ProgramError> {
+        create_and_check(seeds, bump, program_id, expected.key)?;
+        if expected.owner != owner {
+            Err(ProgramError::IllegalOwner)
+        } else {
+            Ok(())
+        }
+    }
+
     fn find_and_check(
         seeds: &'b [&'a [u8]],
         program_id: &Pubkey,
@@ -63,6 +104,69 @@ impl<'a, 'b> SignerPda<'a, 'b> {
     }
 }
 
+/// [`SignerPda`] that owns its seeds, so that it can be stored in structs and
+/// returned from functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedSignerPda {
+    pub pda: Pubkey,
+    pub bump: [u8; 1],
+    pub seeds: Vec<Vec<u8>>,
+}
+
+impl OwnedSignerPda {
+    /// Computes a new PDA and checks whether it matches the expected address.
+    pub fn new_checked(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+        expected: &AccountInfo,
+    ) -> Result<Self, ProgramError> {
+        SignerPda::new_checked(seeds, program_id, expected).map(Self::from)
+    }
+
+    /// Verifies a known bump instead of searching for it. The bump must be
+    /// the stored canonical one, see [`SignerPda::new_with_bump`].
+    pub fn new_with_bump(
+        seeds: &[&[u8]],
+        bump: u8,
+        program_id: &Pubkey,
+        expected: &AccountInfo,
+    ) -> Result<Self, ProgramError> {
+        SignerPda::new_with_bump(seeds, bump, program_id, expected).map(Self::from)
+    }
+
+    /// Returns the signer seeds (seeds + bump seed) of the PDA.
+    pub fn signer_seeds(&self) -> Vec<&[u8]> {
+        let mut signer_seeds = self.seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        signer_seeds.push(&self.bump);
+        signer_seeds
+    }
+}
+
+impl From<SignerPda<'_, '_>> for OwnedSignerPda {
+    fn from(signer_pda: SignerPda) -> Self {
+        Self {
+            pda: signer_pda.pda,
+            bump: signer_pda.bump,
+            seeds: signer_pda.seeds.iter().map(|seed| seed.to_vec()).collect(),
+        }
+    }
+}
+
+fn create_and_check(
+    seeds: &[&[u8]],
+    bump: u8,
+    program_id: &Pubkey,
+    expected: &Pubkey,
+) -> Result<Pubkey, ProgramError> {
+    let bump = [bump];
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(&bump);
+    match Pubkey::create_program_address(&signer_seeds, program_id) {
+        Ok(pda) if &pda == expected => Ok(pda),
+        _ => Err(ProgramError::InvalidSeeds),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -121,4 +225,114 @@ mod test {
 
         assert!(SignerPda::check_owner(seeds, &program_id, &new_owner, &account_info).is_ok());
     }
+
+    #[test]
+    fn test_known_bump() {
+        let program_id = Pubkey::new_unique();
+        let seed_pubkey = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"this is a seed", seed_pubkey.as_ref()];
+        let (pda, bump) = Pubkey::find_program_address(seeds, &program_id);
+
+        let mut data = [];
+        let mut lamports = 1500;
+        let account_info = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            data.as_mut_slice(),
+            &program_id,
+            false,
+            0,
+        );
+        let signer_pda = SignerPda::new_with_bump(seeds, bump, &program_id, &account_info).unwrap();
+        assert_eq!(signer_pda.pda, pda);
+        assert_eq!(
+            signer_pda.signer_seeds(),
+            SignerPda::new_checked(seeds, &program_id, &account_info)
+                .unwrap()
+                .signer_seeds()
+        );
+        // bad bump
+        assert_eq!(
+            SignerPda::new_with_bump(seeds, bump.wrapping_sub(1), &program_id, &account_info)
+                .err()
+                .unwrap(),
+            ProgramError::InvalidSeeds
+        );
+        assert!(SignerPda::check_owner_with_bump(
+            seeds,
+            bump,
+            &program_id,
+            &program_id,
+            &account_info
+        )
+        .is_ok());
+        assert_eq!(
+            SignerPda::check_owner_with_bump(
+                seeds,
+                bump,
+                &program_id,
+                &Pubkey::new_unique(),
+                &account_info
+            ),
+            Err(ProgramError::IllegalOwner)
+        );
+
+        // owned seeds outlive the borrowed ones
+        let owned = {
+            let seed_bytes = seed_pubkey.to_bytes();
+            let seeds: &[&[u8]] = &[b"this is a seed", &seed_bytes];
+            OwnedSignerPda::new_with_bump(seeds, bump, &program_id, &account_info).unwrap()
+        };
+        assert_eq!(owned, OwnedSignerPda::from(signer_pda));
+        assert_eq!(owned.signer_seeds(), signer_pda.signer_seeds());
+    }
+
+    #[test]
+    fn test_non_canonical_bump() {
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"this is a seed"];
+        let (_, canonical_bump) = Pubkey::find_program_address(seeds, &program_id);
+        let (pda, bump) = (0..canonical_bump)
+            .rev()
+            .find_map(|bump| {
+                Pubkey::create_program_address(&[seeds[0], &[bump]], &program_id)
+                    .ok()
+                    .map(|pda| (pda, bump))
+            })
+            .unwrap();
+
+        let mut data = [];
+        let mut lamports = 1500;
+        let account_info = AccountInfo::new(
+            &pda,
+            false,
+            false,
+            &mut lamports,
+            data.as_mut_slice(),
+            &program_id,
+            false,
+            0,
+        );
+        // the address isn't the canonical PDA of the seeds...
+        assert_eq!(
+            SignerPda::new_checked(seeds, &program_id, &account_info)
+                .err()
+                .unwrap(),
+            ProgramError::InvalidSeeds
+        );
+        // ...but it's accepted with the matching non-canonical bump, which is
+        // why the bump must never come from the caller
+        let signer_pda = SignerPda::new_with_bump(seeds, bump, &program_id, &account_info).unwrap();
+        assert_eq!(signer_pda.pda, pda);
+        assert!(SignerPda::check_owner_with_bump(
+            seeds,
+            bump,
+            &program_id,
+            &program_id,
+            &account_info
+        )
+        .is_ok());
+    }
 }
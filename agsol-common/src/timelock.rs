@@ -1,7 +1,15 @@
 use super::*;
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::clock::UnixTimestamp;
-
+use solana_program::clock::{Clock, UnixTimestamp};
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+
+/// Items locked until their expiration date.
+///
+/// [`Self::lock`] and [`Self::unlock_expired`] (re)sort the items by
+/// `expires`, so unsorted timelocks (e.g. written before sorted insertion or
+/// modified via [`Self::locked_items_mut`]) are sorted by the next call of
+/// either of them.
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct Timelock<T: BorshDeserialize + BorshSerialize + MaxSerializedLen, const N: usize>(
     Vec<LockedItem<T>>,
@@ -24,18 +32,75 @@ where
         Self(Vec::new())
     }
 
+    /// Locks an item after the ones expiring not later than it.
     pub fn lock(&mut self, item: T, expiration_date: UnixTimestamp) -> Result<(), TimelockError> {
         if self.0.len() < N {
-            self.0.push(LockedItem {
-                item,
-                expires: expiration_date,
-            });
+            self.sort();
+            let index = self
+                .0
+                .partition_point(|locked| locked.expires <= expiration_date);
+            self.0.insert(
+                index,
+                LockedItem {
+                    item,
+                    expires: expiration_date,
+                },
+            );
             Ok(())
         } else {
             Err(TimelockError::TimelockStorageFull)
         }
     }
 
+    /// Removes the expired items and returns them in order of expiry.
+    pub fn unlock_expired(&mut self, now: UnixTimestamp) -> Vec<T> {
+        self.sort();
+        let expired = self.0.partition_point(|locked| locked.expired(now));
+        self.0.drain(..expired).map(|locked| locked.item).collect()
+    }
+
+    /// Same as [`Self::unlock_expired`], reading the current time from the
+    /// `Clock` sysvar.
+    pub fn unlock_expired_now(&mut self) -> Result<Vec<T>, ProgramError> {
+        let now = Clock::get()?.unix_timestamp;
+        Ok(self.unlock_expired(now))
+    }
+
+    /// Removes the items matching the predicate, keeping their order.
+    pub fn remove_where<F>(&mut self, mut predicate: F) -> Vec<T>
+    where
+        F: FnMut(&LockedItem<T>) -> bool,
+    {
+        let (removed, kept) = std::mem::take(&mut self.0)
+            .into_iter()
+            .partition::<Vec<_>, _>(|locked| predicate(locked));
+        self.0 = kept;
+        removed.into_iter().map(|locked| locked.item).collect()
+    }
+
+    // stable and linear for already sorted items
+    fn sort(&mut self) {
+        self.0.sort_by_key(|locked| locked.expires);
+    }
+
+    /// Returns the earliest expiration date.
+    pub fn next_expiry(&self) -> Option<UnixTimestamp> {
+        self.0.iter().map(|locked| locked.expires).min()
+    }
+
+    /// Returns the latest expiration date of the items matching the
+    /// predicate, i.e. the time until they are all locked.
+    pub fn locked_until<F>(&self, mut predicate: F) -> Option<UnixTimestamp>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.0
+            .iter()
+            .filter(|locked| predicate(&locked.item))
+            .map(|locked| locked.expires)
+            .max()
+    }
+
     pub fn locked_items(&self) -> &Vec<LockedItem<T>> {
         &self.0
     }
@@ -101,4 +166,59 @@ mod test {
         assert!(timelock.lock(Dummy { a: 4, b: -3423, c: [0_u8; 5] }, 32445).is_err());
         assert_eq!(timelock.len(), L);
     }
+
+    #[test]
+    fn unlock() {
+        let mut timelock = Timelock::<u64, 5>::new();
+        assert_eq!(timelock.next_expiry(), None);
+        for (item, expires) in [(1, 30), (2, 10), (3, 20), (4, 10)] {
+            timelock.lock(item, expires).unwrap();
+        }
+        let sorted = |timelock: &Timelock<u64, 5>| {
+            timelock
+                .locked_items()
+                .iter()
+                .map(|locked| (locked.item, locked.expires))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(&timelock), vec![(2, 10), (4, 10), (3, 20), (1, 30)]);
+        assert_eq!(timelock.next_expiry(), Some(10));
+        assert_eq!(timelock.locked_until(|item| item % 2 == 0), Some(10));
+        assert_eq!(timelock.locked_until(|item| *item < 4), Some(30));
+        assert_eq!(timelock.locked_until(|item| *item > 4), None);
+
+        assert!(timelock.unlock_expired(10).is_empty());
+        assert_eq!(timelock.unlock_expired(21), vec![2, 4, 3]);
+        assert_eq!(timelock.next_expiry(), Some(30));
+
+        // unsorted legacy data
+        for (item, expires) in [(5, 8), (7, 50), (8, 5), (9, 8)] {
+            timelock
+                .locked_items_mut()
+                .push(LockedItem { item, expires });
+        }
+        assert_eq!(timelock.next_expiry(), Some(5));
+        assert_eq!(timelock.unlock_expired(9), vec![8, 5, 9]);
+        assert_eq!(sorted(&timelock), vec![(1, 30), (7, 50)]);
+        timelock.remove_where(|locked| locked.item == 7);
+
+        // locking sorts as well
+        timelock.locked_items_mut().insert(
+            0,
+            LockedItem {
+                item: 10,
+                expires: 45,
+            },
+        );
+        timelock.lock(6, 40).unwrap();
+        assert_eq!(sorted(&timelock), vec![(1, 30), (6, 40), (10, 45)]);
+        assert_eq!(timelock.remove_where(|locked| locked.item == 1), vec![1]);
+        assert_eq!(sorted(&timelock), vec![(6, 40), (10, 45)]);
+
+        // the clock sysvar is only available on-chain
+        assert_eq!(
+            timelock.unlock_expired_now(),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+    }
 }
@@ -0,0 +1,313 @@
+use crate::account_state::strip_discriminator;
+use crate::{AccountStateError, MaxSerializedLen};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::borsh::try_from_slice_unchecked;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+
+use std::cmp::Ordering;
+
+/// Account state prefixed with a version byte that is transparently upgraded
+/// to the latest layout upon reading.
+///
+/// The serialized data is laid out as `VERSIONED_DISCRIMINATOR | VERSION | state`.
+/// Older layouts are kept around as separate types and converted into the
+/// latest one via a chain of `migrate_from_vN` conversions in `migrate`.
+///
+/// The accessors are named `read_versioned` and `write_latest` so they don't
+/// clash with [`AccountState`](crate::AccountState) on types implementing
+/// both traits.
+///
+/// # Examples
+/// ```rust
+/// # #[macro_use]
+/// # extern crate agsol_common_derive;
+/// use agsol_common::{AccountStateError, MaxSerializedLen, VersionedAccountState};
+/// use borsh::{BorshDeserialize, BorshSerialize};
+/// use solana_program::borsh::try_from_slice_unchecked;
+/// use solana_program::program_error::ProgramError;
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// struct PoolV1 {
+///     amount: u64,
+/// }
+///
+/// #[derive(BorshSerialize, BorshDeserialize, MaxSerializedLen)]
+/// struct Pool {
+///     amount: u64,
+///     fee: u16,
+/// }
+///
+/// impl Pool {
+///     fn migrate_from_v1(pool: PoolV1) -> Self {
+///         Self {
+///             amount: pool.amount,
+///             fee: 30,
+///         }
+///     }
+/// }
+///
+/// impl VersionedAccountState for Pool {
+///     const VERSION: u8 = 2;
+///
+///     fn migrate(version: u8, data: &[u8]) -> Result<Self, ProgramError> {
+///         match version {
+///             1 => Ok(Self::migrate_from_v1(try_from_slice_unchecked(data)?)),
+///             _ => Err(AccountStateError::UnsupportedVersion.into()),
+///         }
+///     }
+/// }
+/// # fn main() {}
+/// ```
+pub trait VersionedAccountState: BorshSerialize + BorshDeserialize + MaxSerializedLen {
+    /// Tag prefixing the serialized data. Empty by default. Named apart from
+    /// [`AccountState::DISCRIMINATOR`](crate::AccountState::DISCRIMINATOR) so
+    /// the two don't clash on types implementing both traits.
+    const VERSIONED_DISCRIMINATOR: &'static [u8] = &[];
+    /// Version of the latest layout.
+    const VERSION: u8;
+    /// Length of the account data required to hold the largest possible
+    /// state in the latest layout.
+    const ACCOUNT_LEN: usize = Self::VERSIONED_DISCRIMINATOR.len() + 1 + Self::MAX_SERIALIZED_LEN;
+
+    /// Converts the data serialized in an older `version` of the layout into
+    /// the latest one.
+    fn migrate(version: u8, data: &[u8]) -> Result<Self, ProgramError>
+    where
+        Self: Sized,
+    {
+        let _ = (version, data);
+        Err(AccountStateError::UnsupportedVersion.into())
+    }
+
+    /// Reads the state, upgrading it to the latest layout if necessary.
+    fn read_versioned(account: &AccountInfo) -> Result<Self, ProgramError>
+    where
+        Self: Sized,
+    {
+        let data = account.data.borrow();
+        let data = strip_discriminator(&data, Self::VERSIONED_DISCRIMINATOR)?;
+        let (version, data) = data
+            .split_first()
+            .ok_or(ProgramError::AccountDataTooSmall)?;
+        match version.cmp(&Self::VERSION) {
+            Ordering::Equal => Ok(try_from_slice_unchecked(data)?),
+            Ordering::Less => Self::migrate(*version, data),
+            Ordering::Greater => Err(AccountStateError::UnsupportedVersion.into()),
+        }
+    }
+
+    /// Writes the state in the latest layout.
+    fn write_latest(&self, account: &AccountInfo) -> Result<(), ProgramError> {
+        let mut data = account.data.borrow_mut();
+        if data.len() < Self::VERSIONED_DISCRIMINATOR.len() + 1 {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let (discriminator, data) = data.split_at_mut(Self::VERSIONED_DISCRIMINATOR.len());
+        discriminator.copy_from_slice(Self::VERSIONED_DISCRIMINATOR);
+        let (version, data) = data.split_at_mut(1);
+        version[0] = Self::VERSION;
+        self.serialize(&mut &mut data[..])?;
+        Ok(())
+    }
+
+    /// Grows the account data to `ACCOUNT_LEN` if it's smaller, topping up
+    /// the rent exempt balance from the payer. Must be called before writing
+    /// a state that might not fit into an account created for an older
+    /// layout.
+    fn realloc<'a>(
+        account: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        rent: &Rent,
+    ) -> Result<(), ProgramError> {
+        if account.data_len() >= Self::ACCOUNT_LEN {
+            return Ok(());
+        }
+        let required_lamports = rent.minimum_balance(Self::ACCOUNT_LEN);
+        let lamports = account.lamports();
+        if lamports < required_lamports {
+            invoke(
+                &system_instruction::transfer(payer.key, account.key, required_lamports - lamports),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        account.realloc(Self::ACCOUNT_LEN, true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct DummyV1 {
+        amount: u32,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct DummyV2 {
+        amount: u64,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, MaxSerializedLen, Debug, PartialEq)]
+    struct Dummy {
+        amount: u64,
+        fee: u16,
+        reserved: [u8; 16],
+    }
+
+    impl DummyV2 {
+        fn migrate_from_v1(dummy: DummyV1) -> Self {
+            Self {
+                amount: dummy.amount as u64,
+            }
+        }
+    }
+
+    impl Dummy {
+        fn migrate_from_v2(dummy: DummyV2) -> Self {
+            Self {
+                amount: dummy.amount,
+                fee: 30,
+                reserved: [0; 16],
+            }
+        }
+    }
+
+    impl VersionedAccountState for Dummy {
+        const VERSIONED_DISCRIMINATOR: &'static [u8] = b"dummy";
+        const VERSION: u8 = 3;
+
+        fn migrate(version: u8, data: &[u8]) -> Result<Self, ProgramError> {
+            match version {
+                1 => Ok(Self::migrate_from_v2(DummyV2::migrate_from_v1(
+                    try_from_slice_unchecked(data)?,
+                ))),
+                2 => Ok(Self::migrate_from_v2(try_from_slice_unchecked(data)?)),
+                _ => Err(AccountStateError::UnsupportedVersion.into()),
+            }
+        }
+    }
+
+    #[test]
+    fn read_migrate_write() {
+        let key = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let v1 = [b"dummy".as_slice(), &[1], &1234_u32.to_le_bytes()].concat();
+        let mut lamports = 1_000_000_000;
+        let mut data = v1.clone();
+        data.resize(Dummy::ACCOUNT_LEN, 0);
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+        assert_eq!(Dummy::ACCOUNT_LEN, 5 + 1 + 8 + 2 + 16);
+
+        let expected = Dummy {
+            amount: 1234,
+            fee: 30,
+            reserved: [0; 16],
+        };
+        assert_eq!(Dummy::read_versioned(&account_info).unwrap(), expected);
+
+        expected.write_latest(&account_info).unwrap();
+        assert_eq!(account_info.data.borrow()[5], Dummy::VERSION);
+        assert_eq!(Dummy::read_versioned(&account_info).unwrap(), expected);
+
+        // v2 layout
+        let v2 = [b"dummy".as_slice(), &[2], &u64::MAX.to_le_bytes()].concat();
+        account_info.data.borrow_mut()[..v2.len()].copy_from_slice(&v2);
+        assert_eq!(
+            Dummy::read_versioned(&account_info).unwrap().amount,
+            u64::MAX
+        );
+
+        // unknown versions and discriminators
+        account_info.data.borrow_mut()[5] = 4;
+        assert_eq!(
+            Dummy::read_versioned(&account_info),
+            Err(AccountStateError::UnsupportedVersion.into())
+        );
+        account_info.data.borrow_mut()[5] = 0;
+        assert_eq!(
+            Dummy::read_versioned(&account_info),
+            Err(AccountStateError::UnsupportedVersion.into())
+        );
+        account_info.data.borrow_mut()[0] = b'D';
+        assert_eq!(
+            Dummy::read_versioned(&account_info),
+            Err(AccountStateError::InvalidDiscriminator.into())
+        );
+    }
+
+    #[test]
+    fn realloc_only_when_too_small() {
+        let key = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let v1 = [b"dummy".as_slice(), &[1], &1234_u32.to_le_bytes()].concat();
+        let mut lamports = 0;
+        let mut data = v1.clone();
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+        let mut payer_lamports = 0;
+        let mut payer_data = [];
+        let payer = AccountInfo::new(
+            &key,
+            true,
+            true,
+            &mut payer_lamports,
+            &mut payer_data,
+            &program_id,
+            false,
+            0,
+        );
+
+        // the latest layout doesn't fit into the old account, which has to
+        // be reallocated first
+        let dummy = Dummy::read_versioned(&account_info).unwrap();
+        assert_eq!(
+            dummy.write_latest(&account_info),
+            Err(ProgramError::BorshIoError(
+                "failed to write whole buffer".to_owned()
+            ))
+        );
+
+        // accounts that are large enough are left untouched
+        let mut large_data = vec![0; Dummy::ACCOUNT_LEN];
+        let mut large_lamports = 0;
+        let large_account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut large_lamports,
+            &mut large_data,
+            &program_id,
+            false,
+            0,
+        );
+        Dummy::realloc(&large_account, &payer, &payer, &Rent::default()).unwrap();
+        assert_eq!(large_account.data_len(), Dummy::ACCOUNT_LEN);
+        assert_eq!(large_account.lamports(), 0);
+        dummy.write_latest(&large_account).unwrap();
+        assert_eq!(Dummy::read_versioned(&large_account).unwrap(), dummy);
+    }
+}